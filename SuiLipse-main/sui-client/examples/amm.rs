@@ -1,39 +1,15 @@
-#![allow(unused)]
-
 use clap::{Parser, Subcommand};
-use serde::Deserialize;
+use dotenv::dotenv;
 use std::{
-    convert::TryInto,
-    fs::File,
-    io::{BufReader, Read},
+    io::{self, Write},
     path::PathBuf,
-    str::FromStr,
-};
-use sui_sdk::{
-    crypto::{KeystoreType, SuiKeystore},
-    json::SuiJsonValue,
-    rpc_types::{SuiData, SuiObject, SuiObjectRef, SuiRawData, SuiTypeTag},
-    types::parse_sui_type_tag,
-    types::{
-        base_types::{ObjectID, SuiAddress},
-        crypto::Signature,
-        error::SuiError,
-        id::UID,
-        messages::{SingleTransactionKind, Transaction},
-        object::Object,
-    },
-    SuiClient,
 };
-
-use async_trait::async_trait;
-use dotenv::dotenv;
 use sui_lipse::{
+    client::{AmmClient, Client, Executed, DEFAULT_RPC_URL},
     default_keystore_path,
-    state::{CapabilityState, CoinState, NFTState, Pool},
+    type_tag::TypeTag,
 };
-
-const SUI_AMT: u64 = 10_000_000;
-const JRK_AMT: u64 = 100_000_000; // SUI/JRK = 10
+use sui_sdk::types::base_types::{ObjectID, SuiAddress};
 
 #[tokio::main]
 async fn main() -> Result<(), anyhow::Error> {
@@ -42,225 +18,161 @@ async fn main() -> Result<(), anyhow::Error> {
 
     let keystore_path = opts
         .keystore_path
-        .clone() // clone should be omit
+        .clone()
         .unwrap_or_else(default_keystore_path);
 
-    let package_id = &std::env::var("AMM_PACKAGE").expect("should get Jarek::AMM");
-    let suilipse_pkg = opts
-        .suilipse_packagae_id
-        .clone()
-        .unwrap_or(ObjectID::from_hex_literal(&package_id).unwrap());
+    let suilipse_pkg = match opts.suilipse_packagae_id {
+        Some(package_id) => package_id,
+        None => {
+            let package_id = std::env::var("AMM_PACKAGE").expect("should get Jarek::AMM");
+            ObjectID::from_hex_literal(&package_id)?
+        }
+    };
 
-    let amm_client = AmmClient::new(&opts, suilipse_pkg, keystore_path).await?;
+    let amm_client = AmmClient::new(&opts.rpc_server_url, suilipse_pkg, keystore_path).await?;
 
-    println!("signer\n: {:?}\n", &amm_client.get_signer(0));
+    println!("signer\n: {:?}\n", &amm_client.signer(0)?);
 
-    //deseriazlie
     match opts.subcommand {
         AmmCommand::CreatePool {
             capability,
+            pool_list,
+            verifier,
             token_x,
             token_y,
             fee,
-            name,
-            symbol,
         } => {
-            amm_client
-                .create_pool(capability, token_x, token_y, fee, name, symbol)
+            let executed = amm_client
+                .create_pool(capability, pool_list, verifier, token_x, token_y, fee)
                 .await?;
+            print_executed(&executed);
         }
-        AmmCommand::AddLiquidity { pool } => {
-            print!("add liquidity")
+        AmmCommand::AddLiquidity {
+            pool,
+            token_x,
+            amount_x,
+            token_y,
+            amount_y,
+            slippage,
+        } => {
+            let (add, executed) = amm_client
+                .add_liquidity(pool, token_x, amount_x, token_y, amount_y, slippage)
+                .await?;
+            print_executed(&executed);
+            println!(
+                "expected lp: {}, refund x: {}, refund y: {}",
+                add.lp_output, add.refund_x, add.refund_y
+            );
+        }
+        AmmCommand::RemoveLiquidity {
+            pool,
+            lp_token,
+            lp_amount,
+            slippage,
+        } => {
+            let (remove, executed) = amm_client
+                .remove_liquidity(pool, lp_token, lp_amount, slippage)
+                .await?;
+            print_executed(&executed);
+            println!(
+                "expected x: {}, expected y: {}",
+                remove.amount_x, remove.amount_y
+            );
+        }
+        AmmCommand::SwapX {
+            pool,
+            coin,
+            amount,
+            slippage,
+            yes,
+        } => swap(&amm_client, pool, coin, amount, slippage, yes, true).await?,
+        AmmCommand::SwapY {
+            pool,
+            coin,
+            amount,
+            slippage,
+            yes,
+        } => swap(&amm_client, pool, coin, amount, slippage, yes, false).await?,
+        AmmCommand::Zap {
+            pool,
+            coin,
+            slippage,
+            max_loss,
+        } => {
+            let (zap, executed) = amm_client.zap(pool, coin, slippage, max_loss).await?;
+            println!("\nzap: {:?}", &zap);
+            print_executed(&executed);
+            println!(
+                "expected lp: {}, refund x: {}, refund y: {}",
+                zap.add.lp_output, zap.add.refund_x, zap.add.refund_y
+            );
+            println!(
+                "value: {} of {} input, loss: {} bps",
+                zap.value,
+                zap.amount_in,
+                zap.loss_bps()
+            );
         }
-        AmmCommand::RemoveLiquidity { pool } => {
-            print!("add liquidity")
+        AmmCommand::IsGuardian { guardians, address } => {
+            let address = match address {
+                Some(address) => address,
+                None => amm_client.signer(0)?,
+            };
+            let is_guardian = amm_client.is_guardian(guardians, address).await?;
+            println!("{} is guardian: {}", address, is_guardian);
         }
-        AmmCommand::SwapX { pool } => {
-            print!("swap x ")
+        AmmCommand::Cards { collection } => {
+            for card in amm_client.collection_cards(collection).await? {
+                println!("{} {}: {}", card.id(), card.name(), card.url());
+            }
         }
-        AmmCommand::SwapY { pool } => {
-            print!("swap y")
+        AmmCommand::ListPools { pool_list } => {
+            for (idx, pool) in amm_client.pool_ids(pool_list).await?.iter().enumerate() {
+                println!("{}: {}", idx, pool);
+            }
         }
     }
     Ok(())
 }
 
-struct AmmClient {
-    pool_package_id: ObjectID,
-    client: SuiClient,
-    keystore: SuiKeystore,
-}
-
-//TODO: add client trait
-#[async_trait]
-pub trait Client {
-    fn get_object<T>(&self) -> Result<T, anyhow::Error> {
-        todo!()
+/// Print the quote, then sign once confirmed
+async fn swap(
+    amm_client: &AmmClient,
+    pool: ObjectID,
+    coin: Option<ObjectID>,
+    amount: u64,
+    slippage: u64,
+    yes: bool,
+    x_for_y: bool,
+) -> Result<(), anyhow::Error> {
+    let quote = amm_client
+        .quote_swap(pool, amount, slippage, x_for_y)
+        .await?;
+    println!("{}", quote);
+    if !yes && !confirm("sign this swap?")? {
+        println!("swap not signed");
+        return Ok(());
     }
+    let executed = amm_client.execute_swap(pool, coin, &quote, x_for_y).await?;
+    print_executed(&executed);
+    Ok(())
 }
 
-//mirror scripts for calling on-chain smart contract
-#[async_trait]
-trait PoolScript: Sized {
-    async fn create_pool() -> Result<(), anyhow::Error>;
-    async fn add_liquidity() -> Result<(), anyhow::Error>;
-    async fn remove_liquidity() -> Result<(), anyhow::Error>;
-    async fn swap_token_x() -> Result<(), anyhow::Error>;
-    async fn swap_token_y() -> Result<(), anyhow::Error>;
+/// Anything but `y` or `yes` declines
+fn confirm(prompt: &str) -> Result<bool, anyhow::Error> {
+    print!("{} [y/N] ", prompt);
+    io::stdout().flush()?;
+    let mut answer = String::new();
+    io::stdin().read_line(&mut answer)?;
+    Ok(matches!(answer.trim().to_lowercase().as_str(), "y" | "yes"))
 }
 
-impl AmmClient {
-    async fn new(
-        opts: &AmmClientOpts,
-        pool_package_id: ObjectID,
-        keystore_path: PathBuf,
-    ) -> Result<Self, anyhow::Error> {
-        let keystore = KeystoreType::File(keystore_path).init()?;
-        let amm_client = Self {
-            pool_package_id,
-            client: SuiClient::new_rpc_client(&opts.rpc_server_url, None).await?,
-            keystore,
-        };
-
-        Ok(amm_client)
-    }
-
-    pub fn load_file(path: &str) -> PathBuf {
-        match dirs::home_dir() {
-            ///$HOME/dev/sui/SuiLipse
-            Some(v) => v.join("dev").join("sui").join("SuiLipse").join(path),
-            None => panic!("Cannot obtain home directory path"),
-        }
+fn print_executed(executed: &Executed) {
+    for (idx, obj) in executed.created.iter().enumerate() {
+        println!("\n idx: {} - {:?}", idx, obj);
     }
-
-    fn get_signer(&self, idx: usize) -> SuiAddress {
-        self.keystore.addresses()[idx]
-    }
-
-    async fn create_pool(
-        &self,
-        capability: ObjectID,
-        token_x: ObjectID,
-        token_y: ObjectID,
-        fee_percentage: u64,
-        name: String,
-        symbol: String,
-    ) -> Result<(), anyhow::Error> {
-        let signer = self.get_signer(0); // without public to block out high-level control
-
-        self.client
-            .wallet_sync_api()
-            .sync_account_state(signer)
-            .await?;
-
-        //get the state
-        let capability_obj = self
-            .client
-            .read_api()
-            .get_object(capability)
-            .await?
-            .into_object()
-            .unwrap();
-        let token_x_obj = self
-            .client
-            .read_api()
-            .get_object(token_x)
-            .await?
-            .into_object()
-            .unwrap();
-        let token_y_obj = self
-            .client
-            .read_api()
-            .get_object(token_y)
-            .await?
-            .into_object()
-            .unwrap();
-
-        let capability_state: CapabilityState =
-            capability_obj.data.try_as_move().unwrap().deserialize()?;
-        let token_x_state: CoinState = token_x_obj.data.try_as_move().unwrap().deserialize()?;
-        let token_y_state: CoinState = token_y_obj.data.try_as_move().unwrap().deserialize()?;
-
-        println!("\ncap_x_state:{:?}", &capability_state);
-        println!("\ncoin_x_state:{:?}", &token_x_state);
-        println!("\ncoin_y_state:{:?}", &token_y_state);
-
-        let cap_reference = capability_obj.reference.to_object_ref();
-        let cap_obj: Object = capability_obj.try_into()?;
-        let token_x_reference = token_x_obj.reference.to_object_ref();
-        let token_x_obj: Object = token_x_obj.try_into()?;
-        let token_y_reference = token_y_obj.reference.to_object_ref();
-        let token_y_obj: Object = token_y_obj.try_into()?;
-
-        //create tx
-        let foo = cap_obj.data.type_().unwrap();
-        println!("foo{:?}", foo);
-        let type_args = vec![
-            //SuiTypeTag::from(TypeTag::(foo)),
-            SuiTypeTag::from(token_x_obj.get_move_template_type().unwrap()),
-            SuiTypeTag::from(token_y_obj.get_move_template_type().unwrap()),
-        ];
-        println!("signer {}", &signer);
-        let create_pool_call = self
-            .client
-            .transaction_builder()
-            .move_call(
-                signer,
-                self.pool_package_id,
-                "amm_script", //while this is amm_client, for simplicity consideration, we directly called function in nft module
-                "create_pool",
-                type_args,
-                vec![
-                    SuiJsonValue::from_str(&cap_reference.0.to_string())?,
-                    SuiJsonValue::from_str(&token_x_reference.0.to_string())?,
-                    SuiJsonValue::from_str(&token_y_reference.0.to_string())?,
-                    SuiJsonValue::from_str(&fee_percentage.to_string())?,
-                    SuiJsonValue::from_str(&name)?,
-                    SuiJsonValue::from_str(&symbol)?,
-                ],
-                None,
-                10000,
-            )
-            .await?;
-
-        let signer = self.keystore.signer(signer);
-
-        let signature = Signature::new(&create_pool_call, &signer);
-
-        let response = self
-            .client
-            .quorum_driver()
-            .execute_transaction(Transaction::new(create_pool_call, signature))
-            .await?;
-
-        let mutated_obj = response.effects.mutated.iter();
-
-        for (idx, mut_obj) in mutated_obj.enumerate() {
-            println!("\n idx: {} - {:?}", idx, mut_obj.reference);
-        }
-        Ok(())
-    }
-    async fn add_liquidity(
-        &self,
-        pool: ObjectID,
-        token_x: ObjectID,
-        token_y: ObjectID,
-    ) -> Result<(), anyhow::Error> {
-        Ok(())
-    }
-    async fn remove_liquidity(
-        &self,
-        pool: ObjectID,
-        token_x: ObjectID,
-        token_y: ObjectID,
-    ) -> Result<(), anyhow::Error> {
-        Ok(())
-    }
-    async fn swap_x(&self, pool: ObjectID, token_x: ObjectID) -> Result<(), anyhow::Error> {
-        Ok(())
-    }
-    async fn swap_y(&self, pool: ObjectID, token_y: ObjectID) -> Result<(), anyhow::Error> {
-        Ok(())
+    for event in &executed.events {
+        println!("event: {:?}", event);
     }
 }
 
@@ -278,7 +190,7 @@ struct AmmClientOpts {
     suilipse_packagae_id: Option<ObjectID>,
     #[clap(long)]
     keystore_path: Option<PathBuf>,
-    #[clap(long, default_value = "https://fullnode.devnet.sui.io:443")]
+    #[clap(long, default_value = DEFAULT_RPC_URL)]
     rpc_server_url: String,
     #[clap(subcommand)]
     subcommand: AmmCommand,
@@ -291,35 +203,127 @@ enum AmmCommand {
     CreatePool {
         #[clap(long)]
         capability: ObjectID,
+        /// the shared PoolIdsList the new pool is registered in
+        #[clap(long)]
+        pool_list: ObjectID,
+        /// `V` type argument of the pool, e.g. `<package>::amm_pair::AMM_V2`
+        #[clap(long)]
+        verifier: TypeTag,
         #[clap(long)]
         token_x: ObjectID,
         #[clap(long)]
         token_y: ObjectID,
         #[clap(long)]
         fee: u64,
-        #[clap(long)]
-        name: String,
-        #[clap(long)]
-        symbol: String,
     },
     /// Add liquidity by givend pool
     AddLiquidity {
         #[clap(long)]
         pool: ObjectID,
+        /// defaults to the largest coin X of the first address
+        #[clap(long)]
+        token_x: Option<ObjectID>,
+        /// X to deposit, split out of the coin
+        #[clap(long)]
+        amount_x: u64,
+        /// defaults to the largest coin Y of the first address
+        #[clap(long)]
+        token_y: Option<ObjectID>,
+        /// Y to deposit, split out of the coin
+        #[clap(long)]
+        amount_y: u64,
+        /// tolerance of the add_liquidity minimums, in basis points
+        #[clap(long, default_value = "50")]
+        slippage: u64,
     },
     /// Remove liquidity by givend pool
     RemoveLiquidity {
         #[clap(long)]
         pool: ObjectID,
+        /// defaults to the largest LP coin of the pool owned by the first address
+        #[clap(long)]
+        lp_token: Option<ObjectID>,
+        /// LP to burn, split out of the coin
+        #[clap(long)]
+        lp_amount: u64,
+        /// tolerance of the remove_liquidity minimums, in basis points
+        #[clap(long, default_value = "50")]
+        slippage: u64,
     },
     /// Swap token X in given pool
+    ///
+    /// Unprotected on chain: the swap entry takes no minimum output. The quote is shown
+    /// before signing, and the swap is abandoned when the reserves read right before it
+    /// is built give less than the quoted minimum received; the pool can still move
+    /// after that read
     SwapX {
         #[clap(long)]
         pool: ObjectID,
+        /// defaults to the largest coin X of the first address
+        #[clap(long)]
+        coin: Option<ObjectID>,
+        /// amount to sell, split out of the coin
+        #[clap(long)]
+        amount: u64,
+        /// tolerance of the quoted minimum received, in basis points
+        #[clap(long, default_value = "50")]
+        slippage: u64,
+        /// sign without asking for confirmation
+        #[clap(long)]
+        yes: bool,
     },
     /// Swap token Y in given pool
+    ///
+    /// Unprotected on chain: the swap entry takes no minimum output. The quote is shown
+    /// before signing, and the swap is abandoned when the reserves read right before it
+    /// is built give less than the quoted minimum received; the pool can still move
+    /// after that read
     SwapY {
         #[clap(long)]
         pool: ObjectID,
+        /// defaults to the largest coin Y of the first address
+        #[clap(long)]
+        coin: Option<ObjectID>,
+        /// amount to sell, split out of the coin
+        #[clap(long)]
+        amount: u64,
+        /// tolerance of the quoted minimum received, in basis points
+        #[clap(long, default_value = "50")]
+        slippage: u64,
+        /// sign without asking for confirmation
+        #[clap(long)]
+        yes: bool,
+    },
+    /// Add liquidity with a single coin of either side
+    Zap {
+        #[clap(long)]
+        pool: ObjectID,
+        #[clap(long)]
+        coin: ObjectID,
+        /// tolerance of the swap output and the add_liquidity minimums, in basis points
+        #[clap(long, default_value = "50")]
+        slippage: u64,
+        /// largest part of the input the zap may lose, fees and the truncated
+        /// `quote` included, in basis points
+        #[clap(long, default_value = "100")]
+        max_loss: u64,
+    },
+    /// Check whether an address is in the shared Guardians object
+    IsGuardian {
+        #[clap(long)]
+        guardians: ObjectID,
+        /// defaults to the first address of the keystore
+        #[clap(long)]
+        address: Option<SuiAddress>,
+    },
+    /// List the cards of a CardCollection
+    Cards {
+        #[clap(long)]
+        collection: ObjectID,
+    },
+    /// List every pool id of the shared PoolIdsList object
+    ListPools {
+        #[clap(long)]
+        pool_list: ObjectID,
     },
 }
@@ -0,0 +1,326 @@
+//! Checked mirror of `sui_lipse::amm_math`.
+//!
+//! The functions with an on-chain counterpart evaluate in `u64` like the Move
+//! module: an intermediate product the Move VM aborts on is [`MathError::Overflow`]
+//! and the asserts are reported as [`MathError`] with the same meaning. Helpers
+//! without a counterpart, such as [`mul_div`] and [`get_amount_in`], are evaluated in
+//! `u128`/`U256`.
+use crate::isqrt::{self, LiquidityMode};
+use ethnum::U256;
+use std::convert::TryFrom;
+use thiserror::Error;
+
+/// For fees calculation, `fee_percentage` of 1 equals to 0.01%
+pub const FEE_SCALING: u64 = 10000;
+
+#[derive(Error, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MathError {
+    /// a `u64` intermediate or the result overflows, the Move VM aborts with an arithmetic error
+    #[error("arithmetic overflow")]
+    Overflow,
+    /// `EZeroAmount`
+    #[error("input amount is zero")]
+    ZeroAmount,
+    /// `EReservesEmpty`
+    #[error("one of the reserves is empty")]
+    ReservesEmpty,
+    /// `EInsufficientInput`
+    #[error("insufficient input amount")]
+    InsufficientInput,
+    /// `EInsufficientLiquidityBurned`
+    #[error("insufficient liquidity burned")]
+    InsufficientLiquidityBurned,
+    /// fee is greater than its scaling, the Move VM aborts on the subtraction
+    #[error("fee percentage exceeds fee scaling")]
+    InvalidFee,
+    #[error("division by zero")]
+    DivideByZero,
+    /// requested output drains the reserve, there is no on-chain counterpart
+    #[error("insufficient liquidity for the requested output")]
+    InsufficientLiquidity,
+    /// stable swap amplification coefficient of zero
+    #[error("amplification coefficient must be positive")]
+    InvalidAmp,
+}
+
+impl MathError {
+    /// Abort code raised by `amm_math.move`, `None` when the Move VM aborts with an arithmetic error instead
+    pub fn abort_code(&self) -> Option<u64> {
+        match self {
+            MathError::ZeroAmount => Some(0),
+            MathError::ReservesEmpty => Some(1),
+            MathError::InsufficientInput => Some(2),
+            MathError::InsufficientLiquidityBurned => Some(3),
+            MathError::Overflow
+            | MathError::InvalidFee
+            | MathError::DivideByZero
+            | MathError::InsufficientLiquidity
+            | MathError::InvalidAmp => None,
+        }
+    }
+}
+
+pub(crate) fn to_u64(v: u128) -> Result<u64, MathError> {
+    u64::try_from(v).map_err(|_| MathError::Overflow)
+}
+
+/// `a * b` in `u64`, aborting like the Move VM
+fn checked_mul(a: u64, b: u64) -> Result<u64, MathError> {
+    a.checked_mul(b).ok_or(MathError::Overflow)
+}
+
+pub(crate) fn u256_to_u64(v: U256) -> Result<u64, MathError> {
+    if v > U256::from(u64::MAX) {
+        return Err(MathError::Overflow);
+    }
+    Ok(v.as_u64())
+}
+
+/// for fetching pool info
+pub fn get_l(res_x: u64, res_y: u64) -> u64 {
+    isqrt::get_l(res_x, res_y, LiquidityMode::Move)
+}
+
+/// for adding liquidity
+/// b' (optimzied_) = (Y/X) * a, subjected to Y/X = b/a
+pub fn quote(res_1: u64, res_2: u64, one_side_input: u64) -> Result<u64, MathError> {
+    if res_1 == 0 || res_2 == 0 {
+        return Err(MathError::ReservesEmpty);
+    }
+    if one_side_input == 0 {
+        return Err(MathError::InsufficientInput);
+    }
+
+    (res_2 / res_1)
+        .checked_mul(one_side_input)
+        .ok_or(MathError::Overflow)
+}
+
+/// Rounding applied to the last division of a full precision result
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rounding {
+    Floor,
+    Ceil,
+    /// half rounds up
+    Nearest,
+}
+
+/// `a * b / c` evaluated in `u128` with the given rounding
+pub fn mul_div(a: u64, b: u64, c: u64, rounding: Rounding) -> Result<u64, MathError> {
+    if c == 0 {
+        return Err(MathError::DivideByZero);
+    }
+    let numerator = a as u128 * b as u128;
+    let c = c as u128;
+    let (q, r) = (numerator / c, numerator % c);
+    let round_up = match rounding {
+        Rounding::Floor => false,
+        Rounding::Ceil => r != 0,
+        Rounding::Nearest => r >= c - r,
+    };
+
+    to_u64(if round_up { q + 1 } else { q })
+}
+
+/// How `quote` evaluates `(res_2 / res_1) * one_side_input`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QuoteMode {
+    /// truncates the ratio first, bit-exact with `amm_math::quote` on chain
+    OnChain,
+    /// `one_side_input * res_2 / res_1` without losing precision
+    Precise(Rounding),
+}
+
+/// `quote` under the given mode, `QuoteMode::OnChain` is the same as [`quote`]
+pub fn quote_with_mode(
+    res_1: u64,
+    res_2: u64,
+    one_side_input: u64,
+    mode: QuoteMode,
+) -> Result<u64, MathError> {
+    match mode {
+        QuoteMode::OnChain => quote(res_1, res_2, one_side_input),
+        QuoteMode::Precise(rounding) => {
+            if res_1 == 0 || res_2 == 0 {
+                return Err(MathError::ReservesEmpty);
+            }
+            if one_side_input == 0 {
+                return Err(MathError::InsufficientInput);
+            }
+            mul_div(one_side_input, res_2, res_1, rounding)
+        }
+    }
+}
+
+/// On-chain `quote` next to its full precision value
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct QuoteGap {
+    pub on_chain: u64,
+    pub precise: u64,
+}
+
+impl QuoteGap {
+    /// amount the on-chain truncation leaves out, never negative since truncating the ratio only rounds down
+    pub fn gap(&self) -> u64 {
+        self.precise - self.on_chain
+    }
+}
+
+/// Compare the on-chain `quote` against the floor of the exact value
+pub fn quote_gap(res_1: u64, res_2: u64, one_side_input: u64) -> Result<QuoteGap, MathError> {
+    Ok(QuoteGap {
+        on_chain: quote(res_1, res_2, one_side_input)?,
+        precise: quote_with_mode(
+            res_1,
+            res_2,
+            one_side_input,
+            QuoteMode::Precise(Rounding::Floor),
+        )?,
+    })
+}
+
+/// swap
+/// dy = (dx * y) / (dx + x), at dx' = dx(1 - fee)
+pub fn get_output(
+    one_side_input: u64,
+    reserve_in: u64,
+    reserve_out: u64,
+    f: u64,
+    fee_scaling: u64,
+) -> Result<u64, MathError> {
+    if reserve_in == 0 || reserve_out == 0 {
+        return Err(MathError::ReservesEmpty);
+    }
+    if one_side_input == 0 {
+        return Err(MathError::InsufficientInput);
+    }
+    if f > fee_scaling {
+        return Err(MathError::InvalidFee);
+    }
+
+    let dx_fee_deduction = checked_mul(fee_scaling - f, one_side_input)?;
+    let numerator = checked_mul(dx_fee_deduction, reserve_out)?;
+    let denominator = checked_mul(fee_scaling, reserve_in)?
+        .checked_add(dx_fee_deduction)
+        .ok_or(MathError::Overflow)?;
+    if denominator == 0 {
+        return Err(MathError::DivideByZero);
+    }
+
+    Ok(numerator / denominator)
+}
+
+/// exact output swap, the smallest input whose `get_output` reaches `one_side_output`
+/// dx = ceil( x * dy * S / ((S - f) * (y - dy)) )
+pub fn get_amount_in(
+    one_side_output: u64,
+    reserve_in: u64,
+    reserve_out: u64,
+    f: u64,
+    fee_scaling: u64,
+) -> Result<u64, MathError> {
+    if reserve_in == 0 || reserve_out == 0 {
+        return Err(MathError::ReservesEmpty);
+    }
+    if one_side_output == 0 {
+        return Err(MathError::InsufficientInput);
+    }
+    if f >= fee_scaling {
+        return Err(MathError::InvalidFee);
+    }
+    if one_side_output >= reserve_out {
+        return Err(MathError::InsufficientLiquidity);
+    }
+
+    let numerator =
+        U256::from(reserve_in as u128 * one_side_output as u128) * U256::from(fee_scaling);
+    let denominator =
+        U256::from((fee_scaling - f) as u128 * (reserve_out - one_side_output) as u128);
+
+    u256_to_u64((numerator + denominator - 1) / denominator)
+}
+
+/// upper bound of the input for an exact output swap, `slippage` shares the basis points of `fee_percentage`
+pub fn max_amount_in(amount_in: u64, slippage: u64) -> Result<u64, MathError> {
+    let scaled = amount_in as u128 * (FEE_SCALING as u128 + slippage as u128);
+    to_u64((scaled + FEE_SCALING as u128 - 1) / FEE_SCALING as u128)
+}
+
+/// Required input for an exact output swap together with its slippage bound
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ExactOutputQuote {
+    pub amount_out: u64,
+    /// minimum input at the current reserves, rounded up
+    pub amount_in: u64,
+    /// input still worth paying after the price moved by `slippage`
+    pub max_amount_in: u64,
+}
+
+pub fn quote_exact_output(
+    amount_out: u64,
+    reserve_in: u64,
+    reserve_out: u64,
+    f: u64,
+    slippage: u64,
+) -> Result<ExactOutputQuote, MathError> {
+    let amount_in = get_amount_in(amount_out, reserve_in, reserve_out, f, FEE_SCALING)?;
+
+    Ok(ExactOutputQuote {
+        amount_out,
+        amount_in,
+        max_amount_in: max_amount_in(amount_in, slippage)?,
+    })
+}
+
+/// for add_liquidity
+/// min(dx * L / x, dy * L / y)
+pub fn minted_lp(x: u64, y: u64, dx: u64, dy: u64, lp_supply: u64) -> Result<u64, MathError> {
+    if x == 0 || y == 0 {
+        return Err(MathError::ReservesEmpty);
+    }
+    let lp_x = checked_mul(dx, lp_supply)? / x;
+    let lp_y = checked_mul(dy, lp_supply)? / y;
+
+    Ok(lp_x.min(lp_y))
+}
+
+/// for remove_liquidity
+/// (dx, dy) = ((lp_input/ LP_supply) * reserve_x ,(lp_input/ LP_supply) * reserve_y)
+pub fn withdraw_liquidity(
+    res_x: u64,
+    res_y: u64,
+    lp_value: u64,
+    lp_supply: u64,
+) -> Result<(u64, u64), MathError> {
+    if lp_value == 0 {
+        return Err(MathError::ZeroAmount);
+    }
+    if res_x == 0 || res_y == 0 {
+        return Err(MathError::ReservesEmpty);
+    }
+    if lp_supply == 0 {
+        return Err(MathError::DivideByZero);
+    }
+
+    let amount_x = checked_mul(res_x, lp_value)? / lp_supply;
+    let amount_y = checked_mul(res_y, lp_value)? / lp_supply;
+    if amount_x == 0 || amount_y == 0 {
+        return Err(MathError::InsufficientLiquidityBurned);
+    }
+
+    Ok((amount_x, amount_y))
+}
+
+/// wrapping addition used by the cumulative prices, identical to `u128::wrapping_add`
+pub fn overflow_add(a: u128, b: u128) -> u128 {
+    let r = u128::MAX - b;
+    if r < a {
+        return a - r - 1;
+    }
+    let r = u128::MAX - a;
+    if r < b {
+        return b - r - 1;
+    }
+
+    a + b
+}
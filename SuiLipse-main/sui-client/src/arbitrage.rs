@@ -0,0 +1,89 @@
+//! Arbitrage across the pools known to a [`Router`].
+//!
+//! A cycle is a route that starts and ends in the same token, e.g. SUI -> USDC in
+//! one pool and USDC -> SUI in another, or a triangle of three pairs. Its output
+//! `out(a)` is concave in the input, so the profit `out(a) - a` is maximised by a
+//! ternary search over the exact on-chain swap math.
+use crate::router::{Route, Router};
+use std::cmp::Reverse;
+
+/// Profitable cycle sized at its optimal input
+#[derive(Debug, Clone, PartialEq)]
+pub struct Opportunity {
+    /// starts and ends in `route.hops[0].token_in`
+    pub route: Route,
+    /// `route.amount_out - route.amount_in`, in the start token
+    pub profit: u64,
+}
+
+/// Every cycle of at least two and at most `max_hops` pools, each listed once from its lowest pool index
+pub fn cycles(router: &Router, max_hops: usize) -> Vec<(String, Vec<usize>)> {
+    let mut tokens: Vec<&str> = Vec::new();
+    for pool in router.pools() {
+        for token in [&pool.token_x, &pool.token_y].iter() {
+            if !tokens.contains(&token.as_str()) {
+                tokens.push(token);
+            }
+        }
+    }
+
+    let mut cycles = Vec::new();
+    for token in tokens {
+        for path in router.paths(token, token, max_hops) {
+            // the rotation starting at the smallest pool index represents the cycle, the
+            // two tokens of that pool give its two directions
+            if path.len() >= 2 && path.iter().all(|&idx| idx >= path[0]) {
+                cycles.push((token.to_string(), path));
+            }
+        }
+    }
+    cycles
+}
+
+/// Input maximising the profit of `path` starting in `token`, `None` when no input is profitable
+pub fn optimal_input(router: &Router, token: &str, path: &[usize]) -> Option<Opportunity> {
+    let profit = |amount: u64| -> Option<(i128, Route)> {
+        let route = router.quote_exact_in(token, path, amount).ok()?;
+        Some((route.amount_out as i128 - amount as i128, route))
+    };
+    let value = |amount: u64| profit(amount).map(|(p, _)| p).unwrap_or(i128::MIN);
+
+    // trading more than the first reserve only pushes the price against the cycle
+    let first = &router.pools()[path[0]];
+    let upper = if first.token_x == token {
+        first.reserve_x
+    } else {
+        first.reserve_y
+    };
+    let (mut lo, mut hi) = (1u64, upper.max(1));
+    while hi - lo > 2 {
+        let m1 = lo + (hi - lo) / 3;
+        let m2 = hi - (hi - lo) / 3;
+        if value(m1) < value(m2) {
+            lo = m1 + 1;
+        } else {
+            hi = m2;
+        }
+    }
+
+    let best = (lo..=hi).max_by_key(|&amount| value(amount))?;
+    match profit(best) {
+        Some((p, route)) if p > 0 => Some(Opportunity {
+            route,
+            profit: p as u64,
+        }),
+        _ => None,
+    }
+}
+
+/// Profitable cycles of at most `max_hops` pools, most profitable first
+///
+/// Profits are denominated in different start tokens, the order only compares raw amounts.
+pub fn find_opportunities(router: &Router, max_hops: usize) -> Vec<Opportunity> {
+    let mut opportunities: Vec<Opportunity> = cycles(router, max_hops)
+        .into_iter()
+        .filter_map(|(token, path)| optimal_input(router, &token, &path))
+        .collect();
+    opportunities.sort_by_key(|o| Reverse(o.profit));
+    opportunities
+}
@@ -0,0 +1,581 @@
+use super::{connect, json_arg, sui_type_tag, Client, Executed};
+use crate::{
+    amm_math::MathError,
+    report::{min_amount_out, QuoteReport},
+    simulator::{AddLiquidity, PoolSimulator, RemoveLiquidity},
+    state::{
+        Card, CardCollection, CoinState, Field, Guardians, Pool, PoolCapability, PoolIdsList,
+        PoolType, StateError,
+    },
+    type_tag::{StructTag, TypeTag},
+    zap::{zap_x, zap_y, Zap},
+};
+use std::{path::PathBuf, str::FromStr};
+use sui_sdk::{
+    crypto::SuiKeystore,
+    json::SuiJsonValue,
+    rpc_types::SuiTypeTag,
+    types::{
+        base_types::{ObjectID, SuiAddress},
+        id::ID,
+    },
+    SuiClient,
+};
+
+/// Scripts of `sui_lipse::amm` and reads of the package's objects
+pub struct AmmClient {
+    pool_package_id: ObjectID,
+    client: SuiClient,
+    keystore: SuiKeystore,
+}
+
+impl Client for AmmClient {
+    fn sui_client(&self) -> &SuiClient {
+        &self.client
+    }
+
+    fn keystore(&self) -> &SuiKeystore {
+        &self.keystore
+    }
+
+    fn package_id(&self) -> ObjectID {
+        self.pool_package_id
+    }
+}
+
+impl AmmClient {
+    /// `pool_package_id` is the published `sui_lipse` package
+    pub async fn new(
+        rpc_url: &str,
+        pool_package_id: ObjectID,
+        keystore_path: PathBuf,
+    ) -> Result<Self, anyhow::Error> {
+        let (client, keystore) = connect(rpc_url, keystore_path).await?;
+        Ok(AmmClient {
+            pool_package_id,
+            client,
+            keystore,
+        })
+    }
+
+    /// `<V, X, Y>` of `pool`
+    pub async fn pool_type(&self, pool: ObjectID) -> Result<PoolType, anyhow::Error> {
+        Ok(PoolType::from_tag(&self.object_type(pool).await?)?)
+    }
+
+    /// `[V, X, Y]` of `pool`, the type arguments of every pool script
+    pub async fn pool_type_args(&self, pool: ObjectID) -> Result<Vec<SuiTypeTag>, anyhow::Error> {
+        type_args(&self.pool_type(pool).await?)
+    }
+
+    /// `T` of the `Coin<T>` object `coin`
+    pub async fn coin_type(&self, coin: ObjectID) -> Result<TypeTag, anyhow::Error> {
+        let coin_type: StructTag = self.object_type(coin).await?;
+        Ok(coin_type.coin_type()?.clone())
+    }
+
+    /// Create a `Pool<V, X, Y>` from two coins with the `PoolCapability` of the first signer
+    ///
+    /// `verifier` is the `V` type argument, e.g. `<package>::amm_pair::AMM_V2`.
+    pub async fn create_pool(
+        &self,
+        capability: ObjectID,
+        pool_list: ObjectID,
+        verifier: TypeTag,
+        token_x: ObjectID,
+        token_y: ObjectID,
+        fee_percentage: u64,
+    ) -> Result<Executed, anyhow::Error> {
+        let signer = self.signer(0)?;
+        self.sync(signer).await?;
+
+        let _: PoolCapability = self.get_object(capability).await?;
+        let _: PoolIdsList = self.get_object(pool_list).await?;
+        let (_, token_x_type): (CoinState, _) = self.get_object_with_type(token_x).await?;
+        let (_, token_y_type): (CoinState, _) = self.get_object_with_type(token_y).await?;
+
+        let type_args = vec![
+            sui_type_tag(&verifier)?,
+            sui_type_tag(token_x_type.coin_type()?)?,
+            sui_type_tag(token_y_type.coin_type()?)?,
+        ];
+        self.amm_call(
+            signer,
+            "create_pool",
+            type_args,
+            vec![
+                json_arg(capability)?,
+                json_arg(pool_list)?,
+                json_arg(token_x)?,
+                json_arg(token_y)?,
+                json_arg(fee_percentage)?,
+            ],
+        )
+        .await
+    }
+
+    /// Whether `address` is in the `guardians` `VecSet`, i.e. may call `create_capability`
+    ///
+    /// Pools are created by whoever holds a `PoolCapability`. Capabilities are separate
+    /// objects that can be transferred, so holding one says nothing about membership.
+    pub async fn is_guardian(
+        &self,
+        guardians: ObjectID,
+        address: SuiAddress,
+    ) -> Result<bool, anyhow::Error> {
+        let guardians: Guardians = self.get_object(guardians).await?;
+        Ok(guardians.is_guardian(&address))
+    }
+
+    /// Every pool registered in the shared `PoolIdsList`
+    pub async fn pool_ids(&self, pool_list: ObjectID) -> Result<Vec<ObjectID>, anyhow::Error> {
+        let list: PoolIdsList = self.get_object(pool_list).await?;
+        Ok(list.pool_ids().to_vec())
+    }
+
+    /// Cards locked in a `CardCollection`, read from its dynamic field children
+    ///
+    /// Cards whose field could not be read are left out.
+    pub async fn collection_cards(&self, collection: ObjectID) -> Result<Vec<Card>, anyhow::Error> {
+        let collection_state: CardCollection = self.get_object(collection).await?;
+
+        let mut fields: Vec<Field<ID, Card>> = Vec::new();
+        let children = self
+            .client
+            .read_api()
+            .get_objects_owned_by_object(collection)
+            .await?;
+        for child in children {
+            match self.get_object(child.object_id).await {
+                Ok(field) => fields.push(field),
+                Err(err) => match err.downcast_ref::<StateError>() {
+                    // skip children that are not `Field<ID, Card>`
+                    Some(StateError::TypeMismatch { .. }) => {}
+                    _ => return Err(err),
+                },
+            }
+        }
+
+        Ok(collection_state.resolve_cards(fields))
+    }
+
+    /// Deposit a single coin, swap the optimal portion first then add both sides
+    ///
+    /// The zap runs as three transactions, `split_coin`, the swap and `add_liquidity`,
+    /// which are not atomic: when a later step fails the coins of the earlier ones
+    /// stay with the signer. The plan is rejected before signing anything when its
+    /// [`Zap::loss_bps`] exceeds `max_loss_bps`. `swap_token_x` and `swap_token_y`
+    /// take no minimum output, so the swapped coin is checked against the planned
+    /// output reduced by `slippage` basis points before going on, and the deposit is
+    /// simulated again on the pool after the swap to bound the minimums of
+    /// `add_liquidity`.
+    pub async fn zap(
+        &self,
+        pool: ObjectID,
+        coin: ObjectID,
+        slippage: u64,
+        max_loss_bps: u64,
+    ) -> Result<(Zap, Executed), anyhow::Error> {
+        let signer = self.signer(0)?;
+        self.sync(signer).await?;
+
+        let (pool_state, pool_tag): (Pool, _) = self.get_object_with_type(pool).await?;
+        let (coin_state, coin_tag): (CoinState, _) = self.get_object_with_type(coin).await?;
+
+        let pool_type = PoolType::from_tag(&pool_tag)?;
+        let coin_type = coin_tag.coin_type()?;
+        let x_for_y = if *coin_type == pool_type.token_x {
+            true
+        } else if *coin_type == pool_type.token_y {
+            false
+        } else {
+            anyhow::bail!("coin {} is neither side of pool {}", coin, pool);
+        };
+
+        let simulator = PoolSimulator::from(&pool_state);
+        let zap = if x_for_y {
+            zap_x(&simulator, coin_state.balance())?
+        } else {
+            zap_y(&simulator, coin_state.balance())?
+        };
+        if zap.loss_bps() > max_loss_bps {
+            anyhow::bail!(
+                "zap loses {} bps of the input, more than {} bps",
+                zap.loss_bps(),
+                max_loss_bps
+            );
+        }
+
+        let type_args = type_args(&pool_type)?;
+
+        // 1. split out the portion to swap
+        let swap_coin = self.split(signer, coin, zap.swap.input).await?;
+
+        // 2. swap it for the other side
+        let output_coin = *self
+            .amm_call(
+                signer,
+                if x_for_y {
+                    "swap_token_x"
+                } else {
+                    "swap_token_y"
+                },
+                type_args.clone(),
+                vec![json_arg(pool)?, json_arg(swap_coin)?],
+            )
+            .await?
+            .created
+            .first()
+            .ok_or_else(|| anyhow::anyhow!("no coin swapped from {}", swap_coin))?;
+        let output: CoinState = self.get_object(output_coin).await?;
+        let min_output = min_amount_out(zap.swap.output, slippage)?;
+        if output.balance() < min_output {
+            anyhow::bail!(
+                "swap returned {} under the minimum of {}, coins {} and {} are left with {}",
+                output.balance(),
+                min_output,
+                coin,
+                output_coin,
+                signer
+            );
+        }
+
+        // 3. add the rest together with the swap output, priced on the pool after the swap
+        let rest: CoinState = self.get_object(coin).await?;
+        let pool_state: Pool = self.get_object(pool).await?;
+        let (token_x, token_y, value_x, value_y) = if x_for_y {
+            (coin, output_coin, rest.balance(), output.balance())
+        } else {
+            (output_coin, coin, output.balance(), rest.balance())
+        };
+        let add = PoolSimulator::from(&pool_state).add_liquidity(value_x, value_y, 0, 0)?;
+        let executed = self
+            .amm_call(
+                signer,
+                "add_liquidity",
+                type_args,
+                vec![
+                    json_arg(pool)?,
+                    json_arg(token_x)?,
+                    json_arg(token_y)?,
+                    json_arg(min_amount_out(add.amount_x, slippage)?)?,
+                    json_arg(min_amount_out(add.amount_y, slippage)?)?,
+                ],
+            )
+            .await?;
+
+        Ok((zap, executed))
+    }
+
+    /// Deposit `amount_x` and `amount_y` into `pool`, split out of `token_x` and `token_y`,
+    /// the first signer's largest coins by default
+    ///
+    /// The deposit is simulated against the current reserves, `amount_x_min` and
+    /// `amount_y_min` are the simulated amounts reduced by `slippage` basis points.
+    /// The unused part of the larger side is refunded by the pool.
+    pub async fn add_liquidity(
+        &self,
+        pool: ObjectID,
+        token_x: Option<ObjectID>,
+        amount_x: u64,
+        token_y: Option<ObjectID>,
+        amount_y: u64,
+        slippage: u64,
+    ) -> Result<(AddLiquidity, Executed), anyhow::Error> {
+        let signer = self.signer(0)?;
+        self.sync(signer).await?;
+
+        let (pool_state, pool_tag): (Pool, _) = self.get_object_with_type(pool).await?;
+        let pool_type = PoolType::from_tag(&pool_tag)?;
+
+        let (token_x, value_x) = self
+            .pool_coin(signer, token_x, &pool_type.token_x, amount_x)
+            .await?;
+        let (token_y, value_y) = self
+            .pool_coin(signer, token_y, &pool_type.token_y, amount_y)
+            .await?;
+
+        let quote = PoolSimulator::from(&pool_state).add_liquidity(amount_x, amount_y, 0, 0)?;
+        let amount_x_min = min_amount_out(quote.amount_x, slippage)?;
+        let amount_y_min = min_amount_out(quote.amount_y, slippage)?;
+        let token_x = self.take(signer, token_x, value_x, amount_x).await?;
+        let token_y = self.take(signer, token_y, value_y, amount_y).await?;
+
+        let executed = self
+            .amm_call(
+                signer,
+                "add_liquidity",
+                type_args(&pool_type)?,
+                vec![
+                    json_arg(pool)?,
+                    json_arg(token_x)?,
+                    json_arg(token_y)?,
+                    json_arg(amount_x_min)?,
+                    json_arg(amount_y_min)?,
+                ],
+            )
+            .await?;
+
+        Ok((quote, executed))
+    }
+
+    /// Burn `lp_amount` of a `Coin<LP_TOKEN<V, X, Y>>` of `pool`, the first signer's
+    /// largest one by default
+    ///
+    /// The minimums of both sides are the simulated withdrawal reduced by `slippage`
+    /// basis points.
+    pub async fn remove_liquidity(
+        &self,
+        pool: ObjectID,
+        lp_token: Option<ObjectID>,
+        lp_amount: u64,
+        slippage: u64,
+    ) -> Result<(RemoveLiquidity, Executed), anyhow::Error> {
+        let signer = self.signer(0)?;
+        self.sync(signer).await?;
+
+        let (pool_state, pool_tag): (Pool, _) = self.get_object_with_type(pool).await?;
+        let pool_type = PoolType::from_tag(&pool_tag)?;
+
+        let lp_type = TypeTag::from(pool_type.lp_token(self.package_id()));
+        let (lp_token, lp_value) = self
+            .pool_coin(signer, lp_token, &lp_type, lp_amount)
+            .await?;
+
+        let quote = PoolSimulator::from(&pool_state).remove_liquidity(lp_amount, 0, 0)?;
+        let amount_a_min = min_amount_out(quote.amount_x, slippage)?;
+        let amount_b_min = min_amount_out(quote.amount_y, slippage)?;
+        let lp_token = self.take(signer, lp_token, lp_value, lp_amount).await?;
+
+        let executed = self
+            .amm_call(
+                signer,
+                "remove_liquidity",
+                type_args(&pool_type)?,
+                vec![
+                    json_arg(pool)?,
+                    json_arg(lp_token)?,
+                    json_arg(amount_a_min)?,
+                    json_arg(amount_b_min)?,
+                ],
+            )
+            .await?;
+
+        Ok((quote, executed))
+    }
+
+    /// Quote selling `amount` of X, or of Y unless `x_for_y`, against the current
+    /// reserves of `pool`, to show before [`AmmClient::execute_swap`] signs anything
+    pub async fn quote_swap(
+        &self,
+        pool: ObjectID,
+        amount: u64,
+        slippage: u64,
+        x_for_y: bool,
+    ) -> Result<QuoteReport, anyhow::Error> {
+        let pool_state: Pool = self.get_object(pool).await?;
+        Ok(swap_quote(&pool_state, amount, slippage, x_for_y)?)
+    }
+
+    /// Swap `quote.amount_in` split out of `coin`, the first signer's largest coin of
+    /// the input side when `coin` is not given
+    ///
+    /// The swap is unprotected on chain: `swap_token_x` and `swap_token_y` take no
+    /// minimum output. The reserves are read again right before building the
+    /// transaction and the swap is abandoned when the output simulated on them is
+    /// below `quote.min_received`, the pool can still move between that read and the
+    /// execution.
+    pub async fn execute_swap(
+        &self,
+        pool: ObjectID,
+        coin: Option<ObjectID>,
+        quote: &QuoteReport,
+        x_for_y: bool,
+    ) -> Result<Executed, anyhow::Error> {
+        let signer = self.signer(0)?;
+        self.sync(signer).await?;
+
+        let (_, pool_tag): (Pool, _) = self.get_object_with_type(pool).await?;
+        let pool_type = PoolType::from_tag(&pool_tag)?;
+        let input_type = if x_for_y {
+            &pool_type.token_x
+        } else {
+            &pool_type.token_y
+        };
+        let (coin, value) = self
+            .pool_coin(signer, coin, input_type, quote.amount_in)
+            .await?;
+        let input_coin = self.take(signer, coin, value, quote.amount_in).await?;
+
+        // read again after the split, the last read before the swap is built
+        let pool_state: Pool = self.get_object(pool).await?;
+        let fresh = swap_quote(&pool_state, quote.amount_in, quote.slippage, x_for_y)?;
+        if fresh.amount_out < quote.min_received {
+            anyhow::bail!(
+                "pool {} now returns {}, under the quoted minimum of {}",
+                pool,
+                fresh.amount_out,
+                quote.min_received
+            );
+        }
+
+        self.amm_call(
+            signer,
+            if x_for_y {
+                "swap_token_x"
+            } else {
+                "swap_token_y"
+            },
+            type_args(&pool_type)?,
+            vec![json_arg(pool)?, json_arg(input_coin)?],
+        )
+        .await
+    }
+
+    /// `coin` and its balance once it is a `Coin<coin_type>`, or the largest such coin of
+    /// `owner`, failing unless it holds a non-zero `amount` to spend
+    async fn pool_coin(
+        &self,
+        owner: SuiAddress,
+        coin: Option<ObjectID>,
+        coin_type: &TypeTag,
+        amount: u64,
+    ) -> Result<(ObjectID, u64), anyhow::Error> {
+        if amount == 0 {
+            anyhow::bail!("nothing to spend");
+        }
+        let (coin, value) = self.find_coin(owner, coin, coin_type).await?;
+        if amount > value {
+            anyhow::bail!("coin {} holds {}, less than {}", coin, value, amount);
+        }
+        Ok((coin, value))
+    }
+
+    /// `coin` and its balance once it is a `Coin<coin_type>`, or the largest such coin of `owner`
+    async fn find_coin(
+        &self,
+        owner: SuiAddress,
+        coin: Option<ObjectID>,
+        coin_type: &TypeTag,
+    ) -> Result<(ObjectID, u64), anyhow::Error> {
+        let expected = StructTag::coin(coin_type.clone());
+        if let Some(coin) = coin {
+            let (coin_state, coin_tag): (CoinState, _) = self.get_object_with_type(coin).await?;
+            if coin_tag != expected {
+                anyhow::bail!("coin {} is not a {}", coin, expected);
+            }
+            return Ok((coin, coin_state.balance()));
+        }
+
+        let mut selected: Option<(ObjectID, u64)> = None;
+        let owned = self
+            .client
+            .read_api()
+            .get_objects_owned_by_address(owner)
+            .await?;
+        for info in owned {
+            if StructTag::from_str(&info.type_).ok().as_ref() != Some(&expected) {
+                continue;
+            }
+            let coin_state: CoinState = self.get_object(info.object_id).await?;
+            let value = coin_state.balance();
+            match selected {
+                Some((_, max)) if max >= value => {}
+                _ => selected = Some((info.object_id, value)),
+            }
+        }
+        selected.ok_or_else(|| anyhow::anyhow!("{} owns no {}", owner, expected))
+    }
+
+    /// `coin` holding `value` when `amount` is all of it, otherwise `amount` split out of it
+    async fn take(
+        &self,
+        signer: SuiAddress,
+        coin: ObjectID,
+        value: u64,
+        amount: u64,
+    ) -> Result<ObjectID, anyhow::Error> {
+        if amount < value {
+            self.split(signer, coin, amount).await
+        } else {
+            Ok(coin)
+        }
+    }
+
+    /// Split `amount` out of `coin`, returns the new coin
+    async fn split(
+        &self,
+        signer: SuiAddress,
+        coin: ObjectID,
+        amount: u64,
+    ) -> Result<ObjectID, anyhow::Error> {
+        let split_call = self
+            .client
+            .transaction_builder()
+            .split_coin(signer, coin, vec![amount], None, 1000)
+            .await?;
+        self.execute(signer, split_call)
+            .await?
+            .created
+            .first()
+            .copied()
+            .ok_or_else(|| anyhow::anyhow!("no coin split from {}", coin))
+    }
+
+    /// Sign and execute an entry function of the `amm` module
+    async fn amm_call(
+        &self,
+        signer: SuiAddress,
+        function: &str,
+        type_args: Vec<SuiTypeTag>,
+        args: Vec<SuiJsonValue>,
+    ) -> Result<Executed, anyhow::Error> {
+        let call = self
+            .client
+            .transaction_builder()
+            .move_call(
+                signer,
+                self.pool_package_id,
+                "amm",
+                function,
+                type_args,
+                args,
+                None,
+                10000,
+            )
+            .await?;
+        self.execute(signer, call).await
+    }
+}
+
+/// `[V, X, Y]` as Move call type arguments
+fn type_args(pool_type: &PoolType) -> Result<Vec<SuiTypeTag>, anyhow::Error> {
+    pool_type
+        .type_params()
+        .iter()
+        .copied()
+        .map(sui_type_tag)
+        .collect()
+}
+
+/// `amount` of X, or of Y unless `x_for_y`, swapped against the reserves of `pool`
+fn swap_quote(
+    pool: &Pool,
+    amount: u64,
+    slippage: u64,
+    x_for_y: bool,
+) -> Result<QuoteReport, MathError> {
+    let simulator = PoolSimulator::from(pool);
+    let (reserve_in, reserve_out) = if x_for_y {
+        (simulator.reserve_x(), simulator.reserve_y())
+    } else {
+        (simulator.reserve_y(), simulator.reserve_x())
+    };
+    QuoteReport::new(
+        amount,
+        reserve_in,
+        reserve_out,
+        simulator.fee_percentage(),
+        slippage,
+    )
+}
@@ -0,0 +1,128 @@
+use super::{connect, json_arg, sui_type_tag, Client, Executed};
+use crate::state::{CoinState, TreasuryCapState};
+use std::path::PathBuf;
+use sui_sdk::{
+    crypto::SuiKeystore,
+    types::base_types::{ObjectID, SuiAddress},
+    SuiClient,
+};
+
+/// Scripts of the `coin` module, for any fungible token
+pub struct CoinClient {
+    coin_package_id: ObjectID,
+    client: SuiClient,
+    keystore: SuiKeystore,
+}
+
+impl Client for CoinClient {
+    fn sui_client(&self) -> &SuiClient {
+        &self.client
+    }
+
+    fn keystore(&self) -> &SuiKeystore {
+        &self.keystore
+    }
+
+    fn package_id(&self) -> ObjectID {
+        self.coin_package_id
+    }
+}
+
+impl CoinClient {
+    /// `coin_package_id` is the package whose `coin` module is called, `0x2` for the framework
+    pub async fn new(
+        rpc_url: &str,
+        coin_package_id: ObjectID,
+        keystore_path: PathBuf,
+    ) -> Result<Self, anyhow::Error> {
+        let (client, keystore) = connect(rpc_url, keystore_path).await?;
+        Ok(CoinClient {
+            coin_package_id,
+            client,
+            keystore,
+        })
+    }
+
+    /// Mint `amount` with a `TreasuryCap` held by one of the signers, to the holder by
+    /// default, returns the new coin
+    pub async fn mint_and_transfer(
+        &self,
+        treasury_cap: ObjectID,
+        recipient: Option<SuiAddress>,
+        amount: u64,
+    ) -> Result<ObjectID, anyhow::Error> {
+        // TreasuryCap<T>
+        let (_, treasury_cap_type, sender): (TreasuryCapState, _, _) =
+            self.get_object_with_owner(treasury_cap).await?;
+        let recipient = recipient.unwrap_or(sender);
+        self.sync(sender).await?;
+
+        let type_args = vec![sui_type_tag(treasury_cap_type.treasury_cap_type()?)?];
+
+        let mint_and_transfer_call = self
+            .client
+            .transaction_builder()
+            .move_call(
+                sender,
+                self.coin_package_id,
+                "coin",
+                "mint_and_transfer",
+                type_args,
+                vec![
+                    json_arg(treasury_cap)?,
+                    json_arg(amount)?,
+                    json_arg(recipient)?,
+                ],
+                None, // The gateway server will pick a gas object belong to the signer if not provided.
+                1000,
+            )
+            .await?;
+
+        let executed = self.execute(sender, mint_and_transfer_call).await?;
+        executed
+            .created
+            .first()
+            .copied()
+            .ok_or_else(|| anyhow::anyhow!("no coin minted"))
+    }
+
+    /// Transfer a coin owned by one of the signers, to the first signer by default
+    pub async fn transfer(
+        &self,
+        coin: ObjectID,
+        recipient: Option<SuiAddress>,
+    ) -> Result<Executed, anyhow::Error> {
+        let recipient = match recipient {
+            Some(recipient) => recipient,
+            None => self.signer(0)?,
+        };
+
+        let (coin_state, _, owner): (CoinState, _, _) = self.get_object_with_owner(coin).await?;
+        self.sync(owner).await?;
+
+        let transfer_call = self
+            .client
+            .transaction_builder()
+            .transfer_object(owner, coin_state.uid_into(), None, 1000, recipient)
+            .await?;
+
+        self.execute(owner, transfer_call).await
+    }
+
+    /// Merge `coin_b` into `coin_a`
+    pub async fn join(
+        &self,
+        coin_a: ObjectID,
+        coin_b: ObjectID,
+    ) -> Result<Executed, anyhow::Error> {
+        let signer = self.object_owner(coin_a).await?;
+
+        let join_call = self
+            .client
+            .transaction_builder()
+            .merge_coins(signer, coin_a, coin_b, None, 20_000)
+            .await?;
+
+        self.execute(signer, join_call).await
+    }
+}
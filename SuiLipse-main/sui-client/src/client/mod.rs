@@ -0,0 +1,246 @@
+//! RPC clients for the `sui_lipse` package and the `0x2::coin` module.
+//!
+//! [`AmmClient`] and [`CoinClient`] share the [`Client`] trait: a connection to a
+//! full node, a keystore to sign with, typed object reads checked against
+//! [`MoveState`] and transaction execution.
+mod amm;
+mod coin;
+
+pub use amm::AmmClient;
+pub use coin::CoinClient;
+
+use crate::{
+    events::{AmmEvent, EventError},
+    state::{MoveState, StateError},
+    type_tag::{StructTag, TypeTag},
+};
+use async_trait::async_trait;
+use std::{path::PathBuf, str::FromStr};
+use sui_sdk::{
+    crypto::{KeystoreType, SuiKeystore},
+    json::SuiJsonValue,
+    rpc_types::{SuiData, SuiEvent, SuiTypeTag},
+    types::{
+        base_types::{ObjectID, SuiAddress},
+        crypto::Signature,
+        messages::{Transaction, TransactionData},
+        parse_sui_type_tag,
+    },
+    SuiClient,
+};
+
+/// devnet full node
+pub const DEFAULT_RPC_URL: &str = "https://fullnode.devnet.sui.io:443";
+
+/// Open the RPC connection and the file keystore
+pub async fn connect(
+    rpc_url: &str,
+    keystore_path: PathBuf,
+) -> Result<(SuiClient, SuiKeystore), anyhow::Error> {
+    let keystore = KeystoreType::File(keystore_path).init()?;
+    let client = SuiClient::new_rpc_client(rpc_url, None).await?;
+    Ok((client, keystore))
+}
+
+/// Move call argument, an object id, an address or a number
+pub fn json_arg(value: impl ToString) -> Result<SuiJsonValue, anyhow::Error> {
+    SuiJsonValue::from_str(&value.to_string())
+}
+
+/// Move call type argument
+pub fn sui_type_tag(tag: &TypeTag) -> Result<SuiTypeTag, anyhow::Error> {
+    Ok(SuiTypeTag::from(parse_sui_type_tag(&tag.to_string())?))
+}
+
+/// Effects of an executed transaction
+#[derive(Debug, Clone, Default)]
+pub struct Executed {
+    pub created: Vec<ObjectID>,
+    pub mutated: Vec<ObjectID>,
+    /// events of the `amm` module, other events are left out
+    pub events: Vec<AmmEvent>,
+}
+
+#[async_trait]
+pub trait Client: Sync {
+    fn sui_client(&self) -> &SuiClient;
+
+    fn keystore(&self) -> &SuiKeystore;
+
+    /// package the `sui_lipse` state types are checked against
+    fn package_id(&self) -> ObjectID;
+
+    /// `idx`th address of the keystore
+    fn signer(&self, idx: usize) -> Result<SuiAddress, anyhow::Error> {
+        self.keystore()
+            .addresses()
+            .get(idx)
+            .copied()
+            .ok_or_else(|| anyhow::anyhow!("keystore has no address at index {}", idx))
+    }
+
+    /// Force a sync of the state of `address` in the gateway
+    async fn sync(&self, address: SuiAddress) -> Result<(), anyhow::Error> {
+        self.sui_client()
+            .wallet_sync_api()
+            .sync_account_state(address)
+            .await?;
+        Ok(())
+    }
+
+    /// Fetch `object_id` and deserialize it once its type tag is the one of `T`
+    async fn get_object<T: MoveState + Send>(
+        &self,
+        object_id: ObjectID,
+    ) -> Result<T, anyhow::Error> {
+        Ok(self.get_object_with_type(object_id).await?.0)
+    }
+
+    /// Same as [`Client::get_object`], with the type tag of the object, e.g. to read the
+    /// type arguments of a `Pool` or a `Coin` without fetching it again
+    async fn get_object_with_type<T: MoveState + Send>(
+        &self,
+        object_id: ObjectID,
+    ) -> Result<(T, StructTag), anyhow::Error> {
+        let obj = self
+            .sui_client()
+            .read_api()
+            .get_object(object_id)
+            .await?
+            .into_object()?;
+        let move_obj = obj
+            .data
+            .try_as_move()
+            .ok_or(StateError::NotMoveObject(object_id))?;
+        Ok(T::from_fields_with_type(
+            object_id,
+            &move_obj.type_,
+            serde_json::to_value(&move_obj.fields)?,
+            self.package_id(),
+        )?)
+    }
+
+    /// Same as [`Client::get_object_with_type`] for an object owned by an address, with
+    /// that address, e.g. to sign with the owner of a coin or a capability
+    async fn get_object_with_owner<T: MoveState + Send>(
+        &self,
+        object_id: ObjectID,
+    ) -> Result<(T, StructTag, SuiAddress), anyhow::Error> {
+        let obj = self
+            .sui_client()
+            .read_api()
+            .get_object(object_id)
+            .await?
+            .into_object()?;
+        let owner = obj.owner.get_owner_address()?;
+        let move_obj = obj
+            .data
+            .try_as_move()
+            .ok_or(StateError::NotMoveObject(object_id))?;
+        let (state, tag) = T::from_fields_with_type(
+            object_id,
+            &move_obj.type_,
+            serde_json::to_value(&move_obj.fields)?,
+            self.package_id(),
+        )?;
+        Ok((state, tag, owner))
+    }
+
+    /// Same as [`Client::get_object`], decoded from the raw BCS bytes of the object
+    async fn get_raw_object<T: MoveState + Send>(
+        &self,
+        object_id: ObjectID,
+    ) -> Result<T, anyhow::Error> {
+        let obj = self
+            .sui_client()
+            .read_api()
+            .get_raw_object(object_id)
+            .await?
+            .into_object()?;
+        let move_obj = obj
+            .data
+            .try_as_move()
+            .ok_or(StateError::NotMoveObject(object_id))?;
+        Ok(T::from_bcs(
+            object_id,
+            &move_obj.type_,
+            &move_obj.bcs_bytes,
+            self.package_id(),
+        )?)
+    }
+
+    /// Move type of `object_id`
+    async fn object_type(&self, object_id: ObjectID) -> Result<StructTag, anyhow::Error> {
+        let obj = self
+            .sui_client()
+            .read_api()
+            .get_object(object_id)
+            .await?
+            .into_object()?;
+        let move_obj = obj
+            .data
+            .try_as_move()
+            .ok_or(StateError::NotMoveObject(object_id))?;
+        Ok(StructTag::from_str(&move_obj.type_)?)
+    }
+
+    /// Owner of an object owned by an address
+    async fn object_owner(&self, object_id: ObjectID) -> Result<SuiAddress, anyhow::Error> {
+        let obj = self
+            .sui_client()
+            .read_api()
+            .get_object(object_id)
+            .await?
+            .into_object()?;
+        Ok(obj.owner.get_owner_address()?)
+    }
+
+    /// Sign with `signer` and execute, fails when the transaction aborts
+    async fn execute(
+        &self,
+        signer: SuiAddress,
+        tx: TransactionData,
+    ) -> Result<Executed, anyhow::Error> {
+        let signature = Signature::new(&tx, &self.keystore().signer(signer));
+        let response = self
+            .sui_client()
+            .quorum_driver()
+            .execute_transaction(Transaction::new(tx, signature))
+            .await?;
+        let effects = response.effects;
+        if effects.status.is_err() {
+            anyhow::bail!("transaction failed: {:?}", effects.status);
+        }
+
+        let mut events = Vec::new();
+        for event in &effects.events {
+            if let SuiEvent::MoveEvent {
+                type_,
+                fields: Some(fields),
+                ..
+            } = event
+            {
+                match AmmEvent::decode(type_, serde_json::to_value(fields)?) {
+                    Ok(event) => events.push(event),
+                    // events of other modules
+                    Err(EventError::UnknownEvent(_)) => {}
+                    Err(err) => return Err(err.into()),
+                }
+            }
+        }
+
+        Ok(Executed {
+            created: effects
+                .created
+                .iter()
+                .map(|obj| obj.reference.object_id)
+                .collect(),
+            mutated: effects
+                .mutated
+                .iter()
+                .map(|obj| obj.reference.object_id)
+                .collect(),
+            events,
+        })
+    }
+}
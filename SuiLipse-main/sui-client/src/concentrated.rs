@@ -0,0 +1,590 @@
+//! Concentrated liquidity math for prototyping `AMM_V3` pools off-chain.
+//!
+//! Prices follow Uniswap V3: `price = 1.0001^tick` of token Y quoted in token X, and
+//! the pool tracks `sqrt(price)` as a Q64.64 fixed point `u128`, the layout Move
+//! pools on Sui use as there is no native `u160`. Amounts are `u64` like `Coin<T>`,
+//! fees are in basis points of [`FEE_SCALING`] like `fee_percentage`.
+use crate::amm_math::{u256_to_u64, MathError, FEE_SCALING};
+use ethnum::U256;
+use std::collections::BTreeMap;
+use thiserror::Error;
+
+/// sqrt(1.0001^MAX_TICK) still fits the Q64.64 range
+pub const MIN_TICK: i32 = -443636;
+pub const MAX_TICK: i32 = 443636;
+/// `get_sqrt_price_at_tick(MIN_TICK)`
+pub const MIN_SQRT_PRICE: u128 = 4295048017;
+/// `get_sqrt_price_at_tick(MAX_TICK)`
+pub const MAX_SQRT_PRICE: u128 = 79226673515401279992447579062;
+
+const Q64: u128 = 1 << 64;
+
+#[derive(Error, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConcentratedError {
+    #[error("tick {0} out of range")]
+    InvalidTick(i32),
+    #[error("tick range [{0}, {1}) is empty or not aligned to the tick spacing")]
+    InvalidRange(i32, i32),
+    #[error("sqrt price {0} out of range")]
+    InvalidSqrtPrice(u128),
+    #[error("sqrt price limit {0} is on the wrong side of the current price")]
+    InvalidPriceLimit(u128),
+    #[error("liquidity underflow")]
+    LiquidityUnderflow,
+    #[error(transparent)]
+    Math(#[from] MathError),
+}
+
+// 2^128 / sqrt(1.0001)^(2^i), rounded as in Uniswap's TickMath
+const TICK_RATIOS: [u128; 19] = [
+    0xfff97272373d413259a46990580e213a,
+    0xfff2e50f5f656932ef12357cf3c7fdcc,
+    0xffe5caca7e10e4e61c3624eaa0941cd0,
+    0xffcb9843d60f6159c9db58835c926644,
+    0xff973b41fa98c081472e6896dfb254c0,
+    0xff2ea16466c96a3843ec78b326b52861,
+    0xfe5dee046a99a2a811c461f1969c3053,
+    0xfcbe86c7900a88aedcffc83b479aa3a4,
+    0xf987a7253ac413176f2b074cf7815e54,
+    0xf3392b0822b70005940c7a398e4b70f3,
+    0xe7159475a2c29b7443b29c7fa6e889d9,
+    0xd097f3bdfd2022b8845ad8f792aa5825,
+    0xa9f746462d870fdf8a65dc1f90e061e5,
+    0x70d869a156d2a1b890bb3df62baf32f7,
+    0x31be135f97d08fd981231505542fcfa6,
+    0x9aa508b5b7a84e1c677de54f3e99bc9,
+    0x5d6af8dedb81196699c329225ee604,
+    0x2216e584f5fa1ea926041bedfe98,
+    0x48a170391f7dc42444e8fa2,
+];
+
+/// sqrt(1.0001^tick) as Q64.64, rounded up
+pub fn get_sqrt_price_at_tick(tick: i32) -> Result<u128, ConcentratedError> {
+    if !(MIN_TICK..=MAX_TICK).contains(&tick) {
+        return Err(ConcentratedError::InvalidTick(tick));
+    }
+    let abs_tick = tick.unsigned_abs();
+
+    let mut ratio = if abs_tick & 1 != 0 {
+        U256::from(0xfffcb933bd6fad37aa2d162d1a594001u128)
+    } else {
+        U256::ONE << 128u32
+    };
+    for (i, r) in TICK_RATIOS.iter().enumerate() {
+        if abs_tick & (1 << (i + 1)) != 0 {
+            ratio = (ratio * U256::from(*r)) >> 128u32;
+        }
+    }
+    if tick > 0 {
+        ratio = U256::MAX / ratio;
+    }
+
+    // Q128.128 -> Q64.64
+    let rounding = if ratio & U256::from(u64::MAX) != 0 {
+        1
+    } else {
+        0
+    };
+    Ok((ratio >> 64u32).as_u128() + rounding)
+}
+
+/// greatest tick whose sqrt price does not exceed `sqrt_price`
+pub fn get_tick_at_sqrt_price(sqrt_price: u128) -> Result<i32, ConcentratedError> {
+    if !(MIN_SQRT_PRICE..=MAX_SQRT_PRICE).contains(&sqrt_price) {
+        return Err(ConcentratedError::InvalidSqrtPrice(sqrt_price));
+    }
+    let (mut low, mut high) = (MIN_TICK, MAX_TICK);
+    while low < high {
+        let mid = low + (high - low + 1) / 2;
+        if get_sqrt_price_at_tick(mid)? <= sqrt_price {
+            low = mid;
+        } else {
+            high = mid - 1;
+        }
+    }
+    Ok(low)
+}
+
+fn mul_div(a: U256, b: U256, denominator: U256, round_up: bool) -> Result<U256, MathError> {
+    if denominator == 0 {
+        return Err(MathError::DivideByZero);
+    }
+    let product = a.checked_mul(b).ok_or(MathError::Overflow)?;
+    let q = product / denominator;
+    if round_up && product % denominator != 0 {
+        return Ok(q + 1);
+    }
+    Ok(q)
+}
+
+fn sorted(a: u128, b: u128) -> (u128, u128) {
+    if a > b {
+        (b, a)
+    } else {
+        (a, b)
+    }
+}
+
+/// token X between two sqrt prices, L * (sqrt_b - sqrt_a) / (sqrt_a * sqrt_b)
+pub fn get_amount_x_delta(
+    sqrt_a: u128,
+    sqrt_b: u128,
+    liquidity: u128,
+    round_up: bool,
+) -> Result<u64, MathError> {
+    let (sqrt_a, sqrt_b) = sorted(sqrt_a, sqrt_b);
+    if sqrt_a == 0 {
+        return Err(MathError::DivideByZero);
+    }
+    let numerator = U256::from(liquidity) << 64u32;
+    let delta = U256::from(sqrt_b - sqrt_a);
+    let amount = mul_div(numerator, delta, U256::from(sqrt_b), round_up)?;
+    let amount = mul_div(amount, U256::ONE, U256::from(sqrt_a), round_up)?;
+    u256_to_u64(amount)
+}
+
+/// token Y between two sqrt prices, L * (sqrt_b - sqrt_a)
+pub fn get_amount_y_delta(
+    sqrt_a: u128,
+    sqrt_b: u128,
+    liquidity: u128,
+    round_up: bool,
+) -> Result<u64, MathError> {
+    let (sqrt_a, sqrt_b) = sorted(sqrt_a, sqrt_b);
+    let amount = mul_div(
+        U256::from(liquidity),
+        U256::from(sqrt_b - sqrt_a),
+        U256::from(Q64),
+        round_up,
+    )?;
+    u256_to_u64(amount)
+}
+
+fn liquidity_for_amount_x(sqrt_a: u128, sqrt_b: u128, amount_x: u64) -> Result<u128, MathError> {
+    let (sqrt_a, sqrt_b) = sorted(sqrt_a, sqrt_b);
+    let intermediate = mul_div(
+        U256::from(sqrt_a),
+        U256::from(sqrt_b),
+        U256::from(Q64),
+        false,
+    )?;
+    let liquidity = mul_div(
+        U256::from(amount_x),
+        intermediate,
+        U256::from(sqrt_b - sqrt_a),
+        false,
+    )?;
+    u128_from(liquidity)
+}
+
+fn liquidity_for_amount_y(sqrt_a: u128, sqrt_b: u128, amount_y: u64) -> Result<u128, MathError> {
+    let (sqrt_a, sqrt_b) = sorted(sqrt_a, sqrt_b);
+    let liquidity = mul_div(
+        U256::from(amount_y),
+        U256::from(Q64),
+        U256::from(sqrt_b - sqrt_a),
+        false,
+    )?;
+    u128_from(liquidity)
+}
+
+fn u128_from(v: U256) -> Result<u128, MathError> {
+    if v > U256::from(u128::MAX) {
+        return Err(MathError::Overflow);
+    }
+    Ok(v.as_u128())
+}
+
+/// largest liquidity of the range `[sqrt_a, sqrt_b]` fundable by `(amount_x, amount_y)` at `sqrt_price`
+pub fn get_liquidity_for_amounts(
+    sqrt_price: u128,
+    sqrt_a: u128,
+    sqrt_b: u128,
+    amount_x: u64,
+    amount_y: u64,
+) -> Result<u128, MathError> {
+    let (sqrt_a, sqrt_b) = sorted(sqrt_a, sqrt_b);
+    if sqrt_a == sqrt_b {
+        return Err(MathError::DivideByZero);
+    }
+    if sqrt_price <= sqrt_a {
+        liquidity_for_amount_x(sqrt_a, sqrt_b, amount_x)
+    } else if sqrt_price < sqrt_b {
+        let lx = liquidity_for_amount_x(sqrt_price, sqrt_b, amount_x)?;
+        let ly = liquidity_for_amount_y(sqrt_a, sqrt_price, amount_y)?;
+        Ok(lx.min(ly))
+    } else {
+        liquidity_for_amount_y(sqrt_a, sqrt_b, amount_y)
+    }
+}
+
+/// token amounts backing `liquidity` of the range `[sqrt_a, sqrt_b]` at `sqrt_price`
+pub fn get_amounts_for_liquidity(
+    sqrt_price: u128,
+    sqrt_a: u128,
+    sqrt_b: u128,
+    liquidity: u128,
+    round_up: bool,
+) -> Result<(u64, u64), MathError> {
+    let (sqrt_a, sqrt_b) = sorted(sqrt_a, sqrt_b);
+    if sqrt_price <= sqrt_a {
+        Ok((get_amount_x_delta(sqrt_a, sqrt_b, liquidity, round_up)?, 0))
+    } else if sqrt_price < sqrt_b {
+        Ok((
+            get_amount_x_delta(sqrt_price, sqrt_b, liquidity, round_up)?,
+            get_amount_y_delta(sqrt_a, sqrt_price, liquidity, round_up)?,
+        ))
+    } else {
+        Ok((0, get_amount_y_delta(sqrt_a, sqrt_b, liquidity, round_up)?))
+    }
+}
+
+/// sqrt price after `amount` of X (`x_for_y`) or Y enters (`add`) or leaves the pool
+fn get_next_sqrt_price(
+    sqrt_price: u128,
+    liquidity: u128,
+    amount: u64,
+    x_for_y: bool,
+    add: bool,
+) -> Result<u128, MathError> {
+    if liquidity == 0 {
+        return Err(MathError::ReservesEmpty);
+    }
+    let l = U256::from(liquidity) << 64u32;
+    let p = U256::from(sqrt_price);
+    let amount = U256::from(amount);
+    let next = if x_for_y == add {
+        // token X moves: L * P / (L +- amount * P), rounded up
+        let product = amount * p;
+        let denominator = if add {
+            l + product
+        } else {
+            l.checked_sub(product)
+                .filter(|d| *d > 0)
+                .ok_or(MathError::InsufficientLiquidity)?
+        };
+        mul_div(l, p, denominator, true)?
+    } else if add {
+        // token Y enters: P + amount / L, rounded down
+        p + mul_div(amount, U256::from(Q64), U256::from(liquidity), false)?
+    } else {
+        // token Y leaves: P - amount / L, rounded up
+        let quotient = mul_div(amount, U256::from(Q64), U256::from(liquidity), true)?;
+        p.checked_sub(quotient)
+            .filter(|p| *p > 0)
+            .ok_or(MathError::InsufficientLiquidity)?
+    };
+    u128_from(next)
+}
+
+/// Trade size of a swap, the amount is exact on the named side
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SwapAmount {
+    ExactIn(u64),
+    ExactOut(u64),
+}
+
+/// One step of a swap within a single initialized tick range
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SwapStep {
+    pub sqrt_price_next: u128,
+    /// input excluding the fee
+    pub amount_in: u64,
+    pub amount_out: u64,
+    pub fee_amount: u64,
+}
+
+/// Move from `sqrt_price` towards `sqrt_target` with at most `remaining`, `x_for_y` when the price moves down
+pub fn compute_swap_step(
+    sqrt_price: u128,
+    sqrt_target: u128,
+    liquidity: u128,
+    remaining: SwapAmount,
+    fee: u64,
+) -> Result<SwapStep, MathError> {
+    if fee >= FEE_SCALING {
+        return Err(MathError::InvalidFee);
+    }
+    let x_for_y = sqrt_price >= sqrt_target;
+    let input_to_target = |round_up| {
+        if x_for_y {
+            get_amount_x_delta(sqrt_target, sqrt_price, liquidity, round_up)
+        } else {
+            get_amount_y_delta(sqrt_price, sqrt_target, liquidity, round_up)
+        }
+    };
+    let output_to_target = |round_up| {
+        if x_for_y {
+            get_amount_y_delta(sqrt_target, sqrt_price, liquidity, round_up)
+        } else {
+            get_amount_x_delta(sqrt_price, sqrt_target, liquidity, round_up)
+        }
+    };
+
+    let sqrt_price_next = match remaining {
+        SwapAmount::ExactIn(amount) => {
+            let net = (amount as u128 * (FEE_SCALING - fee) as u128 / FEE_SCALING as u128) as u64;
+            // an amount too large for u64 on the way to the target cannot be reached either
+            match input_to_target(true) {
+                Ok(to_target) if net >= to_target => sqrt_target,
+                _ => get_next_sqrt_price(sqrt_price, liquidity, net, x_for_y, true)?,
+            }
+        }
+        SwapAmount::ExactOut(amount) => match output_to_target(false) {
+            Ok(to_target) if amount >= to_target => sqrt_target,
+            _ => get_next_sqrt_price(sqrt_price, liquidity, amount, x_for_y, false)?,
+        },
+    };
+    let reached = sqrt_price_next == sqrt_target;
+
+    let (amount_in, amount_out) = if x_for_y {
+        (
+            get_amount_x_delta(sqrt_price_next, sqrt_price, liquidity, true)?,
+            get_amount_y_delta(sqrt_price_next, sqrt_price, liquidity, false)?,
+        )
+    } else {
+        (
+            get_amount_y_delta(sqrt_price, sqrt_price_next, liquidity, true)?,
+            get_amount_x_delta(sqrt_price, sqrt_price_next, liquidity, false)?,
+        )
+    };
+    let amount_out = match remaining {
+        SwapAmount::ExactOut(amount) => amount_out.min(amount),
+        SwapAmount::ExactIn(_) => amount_out,
+    };
+
+    let fee_amount = match remaining {
+        // the whole remainder is consumed when the target is not reached
+        SwapAmount::ExactIn(amount) if !reached => amount.saturating_sub(amount_in),
+        _ => {
+            let numerator = amount_in as u128 * fee as u128;
+            let denominator = (FEE_SCALING - fee) as u128;
+            ((numerator + denominator - 1) / denominator) as u64
+        }
+    };
+
+    Ok(SwapStep {
+        sqrt_price_next,
+        amount_in,
+        amount_out,
+        fee_amount,
+    })
+}
+
+/// Aggregate of a multi-tick swap
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SwapResult {
+    /// input including the fee
+    pub amount_in: u64,
+    pub amount_out: u64,
+    pub fee_paid: u64,
+    pub sqrt_price: u128,
+    pub tick: i32,
+    pub steps: Vec<SwapStep>,
+}
+
+/// In-memory concentrated liquidity pool, token X is token0 and token Y is token1
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConcentratedPool {
+    sqrt_price: u128,
+    tick: i32,
+    liquidity: u128,
+    fee: u64,
+    tick_spacing: i32,
+    /// liquidity_net of every initialized tick
+    ticks: BTreeMap<i32, i128>,
+}
+
+impl ConcentratedPool {
+    pub fn new(sqrt_price: u128, fee: u64, tick_spacing: i32) -> Result<Self, ConcentratedError> {
+        if fee >= FEE_SCALING {
+            return Err(MathError::InvalidFee.into());
+        }
+        if tick_spacing <= 0 {
+            return Err(ConcentratedError::InvalidRange(0, tick_spacing));
+        }
+        Ok(ConcentratedPool {
+            sqrt_price,
+            tick: get_tick_at_sqrt_price(sqrt_price)?,
+            liquidity: 0,
+            fee,
+            tick_spacing,
+            ticks: BTreeMap::new(),
+        })
+    }
+
+    pub fn sqrt_price(&self) -> u128 {
+        self.sqrt_price
+    }
+
+    pub fn tick(&self) -> i32 {
+        self.tick
+    }
+
+    /// in-range liquidity
+    pub fn liquidity(&self) -> u128 {
+        self.liquidity
+    }
+
+    /// Add (or with a negative `liquidity` remove) a position on `[tick_lower, tick_upper)`,
+    /// returns the token amounts paid in or taken out
+    pub fn update_position(
+        &mut self,
+        tick_lower: i32,
+        tick_upper: i32,
+        liquidity: i128,
+    ) -> Result<(u64, u64), ConcentratedError> {
+        if tick_lower >= tick_upper
+            || tick_lower % self.tick_spacing != 0
+            || tick_upper % self.tick_spacing != 0
+        {
+            return Err(ConcentratedError::InvalidRange(tick_lower, tick_upper));
+        }
+        let sqrt_lower = get_sqrt_price_at_tick(tick_lower)?;
+        let sqrt_upper = get_sqrt_price_at_tick(tick_upper)?;
+
+        let negated = liquidity.checked_neg().ok_or(MathError::Overflow)?;
+        let mut ticks = self.ticks.clone();
+        for (tick, delta) in [(tick_lower, liquidity), (tick_upper, negated)] {
+            let net = ticks.entry(tick).or_insert(0);
+            *net = net.checked_add(delta).ok_or(MathError::Overflow)?;
+            if *net == 0 {
+                ticks.remove(&tick);
+            }
+        }
+        let mut in_range = self.liquidity;
+        if (tick_lower..tick_upper).contains(&self.tick) {
+            in_range = add_delta(in_range, liquidity)?;
+        }
+
+        let amounts = get_amounts_for_liquidity(
+            self.sqrt_price,
+            sqrt_lower,
+            sqrt_upper,
+            liquidity.unsigned_abs(),
+            liquidity > 0,
+        )?;
+        self.ticks = ticks;
+        self.liquidity = in_range;
+        Ok(amounts)
+    }
+
+    /// Swap across initialized ticks until `amount` is used up or `sqrt_price_limit` is hit,
+    /// `x_for_y` sells token X and moves the price down
+    pub fn swap(
+        &mut self,
+        x_for_y: bool,
+        amount: SwapAmount,
+        sqrt_price_limit: u128,
+    ) -> Result<SwapResult, ConcentratedError> {
+        let limit_ok = if x_for_y {
+            sqrt_price_limit < self.sqrt_price && sqrt_price_limit >= MIN_SQRT_PRICE
+        } else {
+            sqrt_price_limit > self.sqrt_price && sqrt_price_limit <= MAX_SQRT_PRICE
+        };
+        if !limit_ok {
+            return Err(ConcentratedError::InvalidPriceLimit(sqrt_price_limit));
+        }
+
+        let mut state = self.clone();
+        let mut result = SwapResult {
+            amount_in: 0,
+            amount_out: 0,
+            fee_paid: 0,
+            sqrt_price: state.sqrt_price,
+            tick: state.tick,
+            steps: Vec::new(),
+        };
+        let mut remaining = amount;
+
+        while !is_spent(remaining) && state.sqrt_price != sqrt_price_limit {
+            let next_tick = if x_for_y {
+                state
+                    .ticks
+                    .range(..=state.tick)
+                    .next_back()
+                    .map(|(t, _)| *t)
+            } else {
+                state.ticks.range(state.tick + 1..).next().map(|(t, _)| *t)
+            };
+            let next_tick = next_tick.unwrap_or(if x_for_y { MIN_TICK } else { MAX_TICK });
+            let sqrt_next = get_sqrt_price_at_tick(next_tick)?;
+            let sqrt_target = if x_for_y {
+                sqrt_next.max(sqrt_price_limit)
+            } else {
+                sqrt_next.min(sqrt_price_limit)
+            };
+
+            if state.liquidity == 0 && !state.ticks.contains_key(&next_tick) {
+                // nothing left to trade against in this direction
+                break;
+            }
+            let step = if state.liquidity == 0 {
+                SwapStep {
+                    sqrt_price_next: sqrt_target,
+                    amount_in: 0,
+                    amount_out: 0,
+                    fee_amount: 0,
+                }
+            } else {
+                compute_swap_step(
+                    state.sqrt_price,
+                    sqrt_target,
+                    state.liquidity,
+                    remaining,
+                    state.fee,
+                )?
+            };
+
+            remaining = match remaining {
+                SwapAmount::ExactIn(left) => {
+                    SwapAmount::ExactIn(left - (step.amount_in + step.fee_amount).min(left))
+                }
+                SwapAmount::ExactOut(left) => SwapAmount::ExactOut(left - step.amount_out),
+            };
+            result.amount_in = checked_add(result.amount_in, step.amount_in + step.fee_amount)?;
+            result.amount_out = checked_add(result.amount_out, step.amount_out)?;
+            result.fee_paid = checked_add(result.fee_paid, step.fee_amount)?;
+
+            state.sqrt_price = step.sqrt_price_next;
+            if step.sqrt_price_next == sqrt_next {
+                // crossing an initialized tick changes the in-range liquidity
+                if let Some(net) = state.ticks.get(&next_tick) {
+                    let delta = if x_for_y { -*net } else { *net };
+                    state.liquidity = add_delta(state.liquidity, delta)?;
+                }
+                state.tick = if x_for_y { next_tick - 1 } else { next_tick };
+            } else {
+                state.tick = get_tick_at_sqrt_price(state.sqrt_price)?;
+            }
+            result.steps.push(step);
+        }
+
+        result.sqrt_price = state.sqrt_price;
+        result.tick = state.tick;
+        *self = state;
+        Ok(result)
+    }
+}
+
+fn is_spent(amount: SwapAmount) -> bool {
+    match amount {
+        SwapAmount::ExactIn(a) | SwapAmount::ExactOut(a) => a == 0,
+    }
+}
+
+fn checked_add(a: u64, b: u64) -> Result<u64, MathError> {
+    a.checked_add(b).ok_or(MathError::Overflow)
+}
+
+fn add_delta(liquidity: u128, delta: i128) -> Result<u128, ConcentratedError> {
+    if delta < 0 {
+        liquidity
+            .checked_sub(delta.unsigned_abs())
+            .ok_or(ConcentratedError::LiquidityUnderflow)
+    } else {
+        Ok(liquidity
+            .checked_add(delta as u128)
+            .ok_or(MathError::Overflow)?)
+    }
+}
@@ -0,0 +1,399 @@
+//! Pricing curves selected by the verifier type `V` of `Pool<V, X, Y>`.
+//!
+//! `amm_pair.move` declares `AMM_V2`, `AMM_V3`, `StableCurve` and `ConstantCurve`;
+//! [`curve_for_verifier`] maps the first, third and fourth to a [`Curve`]. All fees
+//! are in basis points of [`FEE_SCALING`], like `fee_percentage`, and every result is
+//! rounded in favour of the pool.
+use crate::amm_math::{self, to_u64, u256_to_u64, MathError, FEE_SCALING};
+use crate::uq64x64::{UQ64x64, Q64};
+use ethnum::U256;
+
+pub trait Curve {
+    /// amount of `reserve_out` token received for `input`
+    fn get_output(
+        &self,
+        input: u64,
+        reserve_in: u64,
+        reserve_out: u64,
+        fee: u64,
+    ) -> Result<u64, MathError>;
+
+    /// smallest input that receives at least `output`
+    fn get_input(
+        &self,
+        output: u64,
+        reserve_in: u64,
+        reserve_out: u64,
+        fee: u64,
+    ) -> Result<u64, MathError>;
+
+    /// LP minted for depositing `(dx, dy)`, an empty `lp_supply` is the initial deposit
+    fn minted_lp(
+        &self,
+        reserve_x: u64,
+        reserve_y: u64,
+        dx: u64,
+        dy: u64,
+        lp_supply: u64,
+    ) -> Result<u64, MathError>;
+
+    /// marginal price of the `reserve_in` token quoted in the `reserve_out` token, without fee
+    fn spot_price(&self, reserve_in: u64, reserve_out: u64) -> Result<UQ64x64, MathError>;
+}
+
+fn check_swap(amount: u64, reserve_in: u64, reserve_out: u64, fee: u64) -> Result<(), MathError> {
+    if reserve_in == 0 || reserve_out == 0 {
+        return Err(MathError::ReservesEmpty);
+    }
+    if amount == 0 {
+        return Err(MathError::InsufficientInput);
+    }
+    if fee >= FEE_SCALING {
+        return Err(MathError::InvalidFee);
+    }
+    Ok(())
+}
+
+/// input left after the fee is kept by the pool, rounded down
+fn deduct_fee(input: u64, fee: u64) -> u64 {
+    (input as u128 * (FEE_SCALING - fee) as u128 / FEE_SCALING as u128) as u64
+}
+
+/// gross input whose fee deduction leaves `net`, rounded up
+fn add_fee(net: u128, fee: u64) -> Result<u64, MathError> {
+    let scaling = FEE_SCALING as u128;
+    let denominator = (FEE_SCALING - fee) as u128;
+    to_u64((net * scaling + denominator - 1) / denominator)
+}
+
+/// x * y = k, the `AMM_V2` pool of `amm.move`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ConstantProduct;
+
+impl Curve for ConstantProduct {
+    fn get_output(
+        &self,
+        input: u64,
+        reserve_in: u64,
+        reserve_out: u64,
+        fee: u64,
+    ) -> Result<u64, MathError> {
+        amm_math::get_output(input, reserve_in, reserve_out, fee, FEE_SCALING)
+    }
+
+    fn get_input(
+        &self,
+        output: u64,
+        reserve_in: u64,
+        reserve_out: u64,
+        fee: u64,
+    ) -> Result<u64, MathError> {
+        amm_math::get_amount_in(output, reserve_in, reserve_out, fee, FEE_SCALING)
+    }
+
+    fn minted_lp(
+        &self,
+        reserve_x: u64,
+        reserve_y: u64,
+        dx: u64,
+        dy: u64,
+        lp_supply: u64,
+    ) -> Result<u64, MathError> {
+        if lp_supply == 0 {
+            return Ok(amm_math::get_l(dx, dy));
+        }
+        amm_math::minted_lp(reserve_x, reserve_y, dx, dy, lp_supply)
+    }
+
+    fn spot_price(&self, reserve_in: u64, reserve_out: u64) -> Result<UQ64x64, MathError> {
+        if reserve_in == 0 || reserve_out == 0 {
+            return Err(MathError::ReservesEmpty);
+        }
+        UQ64x64::fraction(reserve_out, reserve_in)
+    }
+}
+
+/// x + y = k, every unit trades one to one until a reserve runs dry
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ConstantSum;
+
+impl Curve for ConstantSum {
+    fn get_output(
+        &self,
+        input: u64,
+        reserve_in: u64,
+        reserve_out: u64,
+        fee: u64,
+    ) -> Result<u64, MathError> {
+        check_swap(input, reserve_in, reserve_out, fee)?;
+        let output = deduct_fee(input, fee);
+        if output >= reserve_out {
+            return Err(MathError::InsufficientLiquidity);
+        }
+        Ok(output)
+    }
+
+    fn get_input(
+        &self,
+        output: u64,
+        reserve_in: u64,
+        reserve_out: u64,
+        fee: u64,
+    ) -> Result<u64, MathError> {
+        check_swap(output, reserve_in, reserve_out, fee)?;
+        if output >= reserve_out {
+            return Err(MathError::InsufficientLiquidity);
+        }
+        add_fee(output as u128, fee)
+    }
+
+    fn minted_lp(
+        &self,
+        reserve_x: u64,
+        reserve_y: u64,
+        dx: u64,
+        dy: u64,
+        lp_supply: u64,
+    ) -> Result<u64, MathError> {
+        let deposit = dx as u128 + dy as u128;
+        if lp_supply == 0 {
+            return to_u64(deposit);
+        }
+        let reserves = reserve_x as u128 + reserve_y as u128;
+        if reserves == 0 {
+            return Err(MathError::ReservesEmpty);
+        }
+        to_u64(deposit * lp_supply as u128 / reserves)
+    }
+
+    fn spot_price(&self, reserve_in: u64, reserve_out: u64) -> Result<UQ64x64, MathError> {
+        if reserve_in == 0 || reserve_out == 0 {
+            return Err(MathError::ReservesEmpty);
+        }
+        Ok(UQ64x64::encode(1))
+    }
+}
+
+/// Curve-style stable swap invariant for two coins
+/// A * n^n * (x + y) + D = A * D * n^n + D^(n+1) / (n^n * x * y)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StableSwap {
+    /// amplification coefficient `A`, the larger the flatter the curve around the peg
+    pub amp: u64,
+}
+
+const N_COINS: u128 = 2;
+/// newton iterations before giving up, Curve uses 255 as well
+const MAX_ITERATIONS: usize = 255;
+
+impl StableSwap {
+    pub fn new(amp: u64) -> Self {
+        StableSwap { amp }
+    }
+
+    fn ann(&self) -> Result<U256, MathError> {
+        if self.amp == 0 {
+            return Err(MathError::InvalidAmp);
+        }
+        Ok(U256::from(self.amp as u128 * N_COINS))
+    }
+
+    /// invariant `D` of the reserves
+    pub fn get_d(&self, x: u64, y: u64) -> Result<U256, MathError> {
+        let s = U256::from(x as u128 + y as u128);
+        if s == 0 {
+            return Ok(U256::ZERO);
+        }
+        if x == 0 || y == 0 {
+            return Err(MathError::ReservesEmpty);
+        }
+        let ann = self.ann()?;
+        let n = U256::from(N_COINS);
+        let mut d = s;
+        for _ in 0..MAX_ITERATIONS {
+            let mut d_p = d;
+            d_p = d_p * d / (U256::from(x) * n);
+            d_p = d_p * d / (U256::from(y) * n);
+            let d_prev = d;
+            d = (ann * s + d_p * n) * d / ((ann - 1) * d + (n + 1) * d_p);
+            if abs_diff(d, d_prev) <= 1 {
+                return Ok(d);
+            }
+        }
+        // did not converge, only reachable with reserves far off the u64 range
+        Err(MathError::Overflow)
+    }
+
+    /// other reserve keeping the invariant `d` when one reserve becomes `x`
+    pub fn get_y(&self, x: u128, d: U256) -> Result<U256, MathError> {
+        if x == 0 {
+            return Err(MathError::ReservesEmpty);
+        }
+        let ann = self.ann()?;
+        let n = U256::from(N_COINS);
+        let x = U256::from(x);
+        let c = d * d / (x * n) * d / (ann * n);
+        let b = x + d / ann;
+        let mut y = d;
+        for _ in 0..MAX_ITERATIONS {
+            let y_prev = y;
+            let denominator = (y * 2 + b)
+                .checked_sub(d)
+                .filter(|v| *v > 0)
+                .ok_or(MathError::DivideByZero)?;
+            y = (y * y + c) / denominator;
+            if abs_diff(y, y_prev) <= 1 {
+                return Ok(y);
+            }
+        }
+        // did not converge, only reachable with reserves far off the u64 range
+        Err(MathError::Overflow)
+    }
+}
+
+fn abs_diff(a: U256, b: U256) -> U256 {
+    if a > b {
+        a - b
+    } else {
+        b - a
+    }
+}
+
+/// `num / den` as a `UQ64x64`, both sides are shifted down until the scaling cannot overflow
+fn ratio(mut num: U256, mut den: U256) -> Result<UQ64x64, MathError> {
+    if den == 0 {
+        return Err(MathError::DivideByZero);
+    }
+    while num.leading_zeros() < 64 {
+        num >>= 1;
+        den >>= 1;
+    }
+    let v = num * U256::from(Q64) / den.max(U256::ONE);
+    if v > U256::from(u128::MAX) {
+        return Err(MathError::Overflow);
+    }
+    Ok(UQ64x64::from_raw(v.as_u128()))
+}
+
+impl Curve for StableSwap {
+    fn get_output(
+        &self,
+        input: u64,
+        reserve_in: u64,
+        reserve_out: u64,
+        fee: u64,
+    ) -> Result<u64, MathError> {
+        check_swap(input, reserve_in, reserve_out, fee)?;
+        let d = self.get_d(reserve_in, reserve_out)?;
+        let x = reserve_in as u128 + deduct_fee(input, fee) as u128;
+        let y = self.get_y(x, d)?;
+
+        // one unit is kept against the rounding of the newton steps
+        let output = U256::from(reserve_out)
+            .checked_sub(y + 1)
+            .unwrap_or(U256::ZERO);
+        if output == 0 {
+            return Err(MathError::InsufficientInput);
+        }
+        u256_to_u64(output)
+    }
+
+    fn get_input(
+        &self,
+        output: u64,
+        reserve_in: u64,
+        reserve_out: u64,
+        fee: u64,
+    ) -> Result<u64, MathError> {
+        check_swap(output, reserve_in, reserve_out, fee)?;
+        if output >= reserve_out {
+            return Err(MathError::InsufficientLiquidity);
+        }
+        let d = self.get_d(reserve_in, reserve_out)?;
+        let x = self.get_y((reserve_out - output) as u128, d)?;
+        let net = x
+            .checked_sub(U256::from(reserve_in))
+            .ok_or(MathError::InsufficientLiquidity)?
+            + 1;
+        if net > U256::from(u64::MAX) {
+            return Err(MathError::Overflow);
+        }
+        add_fee(net.as_u128(), fee)
+    }
+
+    fn minted_lp(
+        &self,
+        reserve_x: u64,
+        reserve_y: u64,
+        dx: u64,
+        dy: u64,
+        lp_supply: u64,
+    ) -> Result<u64, MathError> {
+        let x = reserve_x.checked_add(dx).ok_or(MathError::Overflow)?;
+        let y = reserve_y.checked_add(dy).ok_or(MathError::Overflow)?;
+        let d1 = self.get_d(x, y)?;
+        if lp_supply == 0 {
+            return u256_to_u64(d1);
+        }
+        let d0 = self.get_d(reserve_x, reserve_y)?;
+        if d0 == 0 {
+            return Err(MathError::ReservesEmpty);
+        }
+        u256_to_u64(U256::from(lp_supply) * (d1 - d0) / d0)
+    }
+
+    fn spot_price(&self, reserve_in: u64, reserve_out: u64) -> Result<UQ64x64, MathError> {
+        // -dy/dx of the invariant: (y / x) * (4A'xxy + D^3) / (4A'xyy + D^3)
+        let d = self.get_d(reserve_in, reserve_out)?;
+        let ann = self.ann()?;
+        let (x, y) = (U256::from(reserve_in), U256::from(reserve_out));
+        // `4A'xxy` passes 2^256 with both reserves near `u64::MAX` and a large amp
+        let mul = |a: U256, b: U256| a.checked_mul(b).ok_or(MathError::Overflow);
+        let add = |a: U256, b: U256| a.checked_add(b).ok_or(MathError::Overflow);
+        let d3 = mul(mul(d, d)?, d)?;
+        let axy = mul(mul(mul(ann, U256::from(4u8))?, x)?, y)?;
+        let num = add(mul(axy, x)?, d3)?;
+        let den = add(mul(axy, y)?, d3)?;
+
+        let curvature = ratio(num, den)?.to_u128();
+        let price = UQ64x64::fraction(reserve_out, reserve_in)?.to_u128();
+        let v = U256::from(price) * U256::from(curvature) / U256::from(Q64);
+        if v > U256::from(u128::MAX) {
+            return Err(MathError::Overflow);
+        }
+        Ok(UQ64x64::from_raw(v.as_u128()))
+    }
+}
+
+/// Verifier structs declared in `amm_pair.move`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Verifier {
+    AmmV2,
+    AmmV3,
+    StableCurve,
+    ConstantCurve,
+}
+
+impl Verifier {
+    /// accepts the bare struct name or a full type such as `0x..::amm_pair::StableCurve`
+    pub fn from_type_name(type_name: &str) -> Option<Self> {
+        match type_name.rsplit("::").next()? {
+            "AMM_V2" => Some(Verifier::AmmV2),
+            "AMM_V3" => Some(Verifier::AmmV3),
+            "StableCurve" => Some(Verifier::StableCurve),
+            "ConstantCurve" => Some(Verifier::ConstantCurve),
+            _ => None,
+        }
+    }
+}
+
+/// Curve used to quote a pool whose first type argument is `verifier`,
+/// `None` for `AMM_V3` which does not trade on reserves alone
+pub fn curve_for_verifier(verifier: &str, amp: u64) -> Option<Box<dyn Curve>> {
+    match Verifier::from_type_name(verifier)? {
+        Verifier::AmmV2 => Some(Box::new(ConstantProduct)),
+        Verifier::StableCurve => Some(Box::new(StableSwap::new(amp))),
+        Verifier::ConstantCurve => Some(Box::new(ConstantSum)),
+        Verifier::AmmV3 => None,
+    }
+}
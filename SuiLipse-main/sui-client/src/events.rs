@@ -0,0 +1,170 @@
+//! Events emitted by `sui_lipse::amm`.
+//!
+//! A `MoveEvent` carries its type as a string, e.g.
+//! `0x8a::amm::SwapEvent<0x8a::verifier::V, 0x2::sui::SUI, 0x3::usdc::USDC>`, and
+//! its fields as JSON. [`AmmEvent::decode`] parses the type as a [`StructTag`], picks
+//! the payload struct from its name and keeps the type arguments of the pool events
+//! as a [`PoolType`].
+use crate::{
+    state::PoolType,
+    type_tag::{StructTag, TypeTagError},
+};
+use serde::{Deserialize, Deserializer};
+use std::str::FromStr;
+use sui_sdk::types::base_types::{ObjectID, SuiAddress};
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum EventError {
+    /// not one of the events of the `amm` module
+    #[error("unknown event type {0}")]
+    UnknownEvent(String),
+    /// a pool event without its `<V, X, Y>` type arguments
+    #[error("expected type arguments <V, X, Y> in {0}")]
+    TypeArguments(String),
+    #[error(transparent)]
+    TypeTag(#[from] TypeTagError),
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+}
+
+/// `sui::object::ID`, `{ "bytes": id }` or the bare id in JSON
+fn deserialize_id<'de, D: Deserializer<'de>>(deserializer: D) -> Result<ObjectID, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Id {
+        Bytes { bytes: ObjectID },
+        Flat(ObjectID),
+    }
+
+    if deserializer.is_human_readable() {
+        Ok(match Id::deserialize(deserializer)? {
+            Id::Bytes { bytes } => bytes,
+            Id::Flat(id) => id,
+        })
+    } else {
+        ObjectID::deserialize(deserializer)
+    }
+}
+
+/// Payload of `PoolCapabilityCreatedEvent`
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PoolCapabilityCreatedEvent {
+    #[serde(deserialize_with = "deserialize_id")]
+    pub pool_capability_id: ObjectID,
+}
+
+/// Payload of `PoolCreatedEvent`
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PoolCreatedEvent {
+    #[serde(deserialize_with = "deserialize_id")]
+    pub pool_id: ObjectID,
+    pub creator: SuiAddress,
+}
+
+/// Payload of `LiquidityAddedEvent<V, X, Y>`
+///
+/// The `add_liquidity` entry destructures `add_liquidity_`'s `(coin, lp_output, amount_x,
+/// amount_y)` as `(coin, amount_a, amount_b, lp_output)`, so the fields do not hold what
+/// their names say. Read them through [`amount_x`](Self::amount_x),
+/// [`amount_y`](Self::amount_y) and [`lp_output`](Self::lp_output).
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LiquidityAddedEvent {
+    /// LP minted
+    pub added_amount_0: u64,
+    /// X deposited
+    pub added_amount_1: u64,
+    /// Y deposited
+    pub lp_tokens_received: u64,
+}
+impl LiquidityAddedEvent {
+    pub fn amount_x(&self) -> u64 {
+        self.added_amount_1
+    }
+    pub fn amount_y(&self) -> u64 {
+        self.lp_tokens_received
+    }
+    pub fn lp_output(&self) -> u64 {
+        self.added_amount_0
+    }
+}
+
+/// Payload of `LiquidityRemovedEvent<V, X, Y>`
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LiquidityRemovedEvent {
+    /// X withdrawn
+    pub returned_amount_0: u64,
+    /// Y withdrawn
+    pub returned_amount_1: u64,
+    pub lp_tokens_removed: u64,
+}
+
+/// Payload of `SwapEvent<V, X, Y>`, emitted by both `swap_token_x` and `swap_token_y`
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SwapEvent {
+    pub coin0_in: u64,
+    pub coin1_out: u64,
+}
+
+/// Payload of `OracleUpdatedEvent<V, X, Y>`
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OracleUpdatedEvent {
+    #[serde(with = "crate::serde_str::u128")]
+    pub last_price_cumulative_0: u128,
+    #[serde(with = "crate::serde_str::u128")]
+    pub last_price_cumulative_1: u128,
+}
+
+/// Any event of the `amm` module, pool events keep the pool's type arguments
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AmmEvent {
+    PoolCapabilityCreated(PoolCapabilityCreatedEvent),
+    PoolCreated(PoolCreatedEvent),
+    LiquidityAdded(PoolType, LiquidityAddedEvent),
+    LiquidityRemoved(PoolType, LiquidityRemovedEvent),
+    Swap(PoolType, SwapEvent),
+    OracleUpdated(PoolType, OracleUpdatedEvent),
+}
+
+impl AmmEvent {
+    /// Decode the `fields` of a `MoveEvent` of type `type_`
+    pub fn decode(type_: &str, fields: serde_json::Value) -> Result<Self, EventError> {
+        let tag = StructTag::from_str(type_)?;
+        if tag.module != "amm" {
+            return Err(EventError::UnknownEvent(type_.to_string()));
+        }
+        let pool_type = || {
+            PoolType::from_params(&tag.type_params)
+                .ok_or_else(|| EventError::TypeArguments(type_.to_string()))
+        };
+
+        Ok(match tag.name.as_str() {
+            "PoolCapabilityCreatedEvent" => {
+                AmmEvent::PoolCapabilityCreated(serde_json::from_value(fields)?)
+            }
+            "PoolCreatedEvent" => AmmEvent::PoolCreated(serde_json::from_value(fields)?),
+            "LiquidityAddedEvent" => {
+                AmmEvent::LiquidityAdded(pool_type()?, serde_json::from_value(fields)?)
+            }
+            "LiquidityRemovedEvent" => {
+                AmmEvent::LiquidityRemoved(pool_type()?, serde_json::from_value(fields)?)
+            }
+            "SwapEvent" => AmmEvent::Swap(pool_type()?, serde_json::from_value(fields)?),
+            "OracleUpdatedEvent" => {
+                AmmEvent::OracleUpdated(pool_type()?, serde_json::from_value(fields)?)
+            }
+            _ => return Err(EventError::UnknownEvent(type_.to_string())),
+        })
+    }
+
+    /// type arguments of the emitting pool, `None` for the admin events
+    pub fn pool_type(&self) -> Option<&PoolType> {
+        match self {
+            AmmEvent::PoolCapabilityCreated(_) | AmmEvent::PoolCreated(_) => None,
+            AmmEvent::LiquidityAdded(ty, _)
+            | AmmEvent::LiquidityRemoved(ty, _)
+            | AmmEvent::Swap(ty, _)
+            | AmmEvent::OracleUpdated(ty, _) => Some(ty),
+        }
+    }
+}
@@ -0,0 +1,275 @@
+//! Protocol fee switch and fee accounting, modeled off-chain before touching `amm.move`.
+//!
+//! Today every swap fee stays in the reserves and accrues to the LPs. With a fee
+//! switch a share of that growth is minted as LP tokens to a treasury, following
+//! Uniswap V2's `_mintFee`: the pool remembers `k_last = x * y` after the last
+//! liquidity event, and on the next one mints the treasury its share of the
+//! `sqrt(k)` growth since then.
+use crate::{
+    amm_math::{self, to_u64, u256_to_u64, MathError, FEE_SCALING},
+    isqrt::Sqrt,
+    simulator::{AddLiquidity, AmmError, PoolSimulator, RemoveLiquidity, Swap},
+};
+use ethnum::U256;
+
+/// Share of the swap fees going to the protocol, Uniswap V2 uses 1/6
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProtocolFee {
+    numerator: u64,
+    denominator: u64,
+}
+
+impl ProtocolFee {
+    pub fn new(numerator: u64, denominator: u64) -> Result<Self, MathError> {
+        if denominator == 0 {
+            return Err(MathError::DivideByZero);
+        }
+        if numerator > denominator {
+            return Err(MathError::InvalidFee);
+        }
+        Ok(ProtocolFee {
+            numerator,
+            denominator,
+        })
+    }
+
+    pub fn numerator(&self) -> u64 {
+        self.numerator
+    }
+
+    pub fn denominator(&self) -> u64 {
+        self.denominator
+    }
+
+    /// LP minted to the treasury for the growth from `k_last` to `reserve_x * reserve_y`
+    /// m = L * n * (sqrt(k) - sqrt(k_last)) / (sqrt(k) * (d - n) + sqrt(k_last) * n)
+    pub fn mint_fee(
+        &self,
+        reserve_x: u64,
+        reserve_y: u64,
+        lp_supply: u64,
+        k_last: u128,
+    ) -> Result<u64, MathError> {
+        if k_last == 0 || self.numerator == 0 {
+            return Ok(0);
+        }
+        let root_k = (reserve_x as u128 * reserve_y as u128).sqrt_floor();
+        let root_k_last = k_last.sqrt_floor();
+        if root_k <= root_k_last {
+            return Ok(0);
+        }
+
+        let n = U256::from(self.numerator);
+        let numerator = U256::from(lp_supply) * n * U256::from(root_k - root_k_last);
+        let denominator = U256::from(root_k) * U256::from(self.denominator - self.numerator)
+            + U256::from(root_k_last) * n;
+        if denominator == 0 {
+            return Err(MathError::DivideByZero);
+        }
+
+        u256_to_u64(numerator / denominator)
+    }
+
+    /// protocol part of a fee amount, rounded down
+    pub fn share_of(&self, fee: u128) -> u128 {
+        fee * self.numerator as u128 / self.denominator as u128
+    }
+}
+
+/// Cumulative swap volume and fees of a pool, fed from its swap history
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FeeLedger {
+    pub swaps: u64,
+    /// input volume in X, from `swap_token_x`
+    pub volume_x: u128,
+    /// input volume in Y, from `swap_token_y`
+    pub volume_y: u128,
+    /// `amount_in * fee_percentage / FEE_SCALING`, charged in the input token
+    pub fees_x: u128,
+    pub fees_y: u128,
+}
+
+impl FeeLedger {
+    /// Record one swap, e.g. from the `coin0_in` of a `SwapEvent`
+    pub fn record(&mut self, x_for_y: bool, amount_in: u64, fee_percentage: u64) {
+        let fee = amount_in as u128 * fee_percentage as u128 / FEE_SCALING as u128;
+        self.swaps += 1;
+        if x_for_y {
+            self.volume_x += amount_in as u128;
+            self.fees_x += fee;
+        } else {
+            self.volume_y += amount_in as u128;
+            self.fees_y += fee;
+        }
+    }
+
+    /// `(fees_x, fees_y)` left to the LPs under `protocol_fee`
+    pub fn lp_fees(&self, protocol_fee: Option<ProtocolFee>) -> (u128, u128) {
+        let (protocol_x, protocol_y) = self.protocol_fees(protocol_fee);
+        (self.fees_x - protocol_x, self.fees_y - protocol_y)
+    }
+
+    /// `(fees_x, fees_y)` taken by the protocol under `protocol_fee`
+    pub fn protocol_fees(&self, protocol_fee: Option<ProtocolFee>) -> (u128, u128) {
+        match protocol_fee {
+            Some(p) => (p.share_of(self.fees_x), p.share_of(self.fees_y)),
+            None => (0, 0),
+        }
+    }
+}
+
+/// [`PoolSimulator`] with a fee switch, minting the protocol share before every liquidity event
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FeeSwitchPool {
+    pool: PoolSimulator,
+    protocol_fee: Option<ProtocolFee>,
+    k_last: u128,
+    treasury_lp: u64,
+    ledger: FeeLedger,
+}
+
+impl FeeSwitchPool {
+    /// fee growth is counted from this snapshot on, `None` keeps today's behaviour
+    pub fn new(pool: PoolSimulator, protocol_fee: Option<ProtocolFee>) -> Self {
+        let (x, y, _) = pool.reserves();
+        FeeSwitchPool {
+            pool,
+            protocol_fee,
+            k_last: if protocol_fee.is_some() {
+                x as u128 * y as u128
+            } else {
+                0
+            },
+            treasury_lp: 0,
+            ledger: FeeLedger::default(),
+        }
+    }
+
+    pub fn pool(&self) -> &PoolSimulator {
+        &self.pool
+    }
+
+    pub fn k_last(&self) -> u128 {
+        self.k_last
+    }
+
+    /// LP tokens minted to the treasury so far
+    pub fn treasury_lp(&self) -> u64 {
+        self.treasury_lp
+    }
+
+    pub fn ledger(&self) -> &FeeLedger {
+        &self.ledger
+    }
+
+    /// LP the treasury would receive if liquidity changed now
+    pub fn pending_protocol_lp(&self) -> Result<u64, MathError> {
+        let (x, y, lp_supply) = self.pool.reserves();
+        match self.protocol_fee {
+            Some(p) => p.mint_fee(x, y, lp_supply, self.k_last),
+            None => Ok(0),
+        }
+    }
+
+    fn mint_fee(&mut self) -> Result<(), AmmError> {
+        let minted = self.pending_protocol_lp()?;
+        if minted > 0 {
+            let (x, y, lp_supply) = self.pool.reserves();
+            let lp_supply = lp_supply.checked_add(minted).ok_or(MathError::Overflow)?;
+            self.pool = PoolSimulator::from_reserves(x, y, lp_supply, self.pool.fee_percentage());
+            self.treasury_lp += minted;
+        }
+        Ok(())
+    }
+
+    fn update_k_last(&mut self) {
+        if self.protocol_fee.is_some() {
+            let (x, y, _) = self.pool.reserves();
+            self.k_last = x as u128 * y as u128;
+        }
+    }
+
+    /// Mint the pending protocol share without a liquidity change
+    pub fn collect(&mut self) -> Result<u64, AmmError> {
+        let before = self.treasury_lp;
+        self.mint_fee()?;
+        self.update_k_last();
+        Ok(self.treasury_lp - before)
+    }
+
+    pub fn swap_x(&mut self, token_x: u64) -> Result<Swap, AmmError> {
+        let swap = self.pool.swap_x(token_x)?;
+        self.ledger
+            .record(true, swap.input, self.pool.fee_percentage());
+        Ok(swap)
+    }
+
+    pub fn swap_y(&mut self, token_y: u64) -> Result<Swap, AmmError> {
+        let swap = self.pool.swap_y(token_y)?;
+        self.ledger
+            .record(false, swap.input, self.pool.fee_percentage());
+        Ok(swap)
+    }
+
+    pub fn add_liquidity(
+        &mut self,
+        token_x: u64,
+        token_y: u64,
+        amount_x_min: u64,
+        amount_y_min: u64,
+    ) -> Result<AddLiquidity, AmmError> {
+        let snapshot = *self;
+        self.mint_fee()?;
+        match self
+            .pool
+            .add_liquidity(token_x, token_y, amount_x_min, amount_y_min)
+        {
+            Ok(added) => {
+                self.update_k_last();
+                Ok(added)
+            }
+            Err(e) => {
+                *self = snapshot;
+                Err(e)
+            }
+        }
+    }
+
+    pub fn remove_liquidity(
+        &mut self,
+        lp_value: u64,
+        amount_a_min: u64,
+        amount_b_min: u64,
+    ) -> Result<RemoveLiquidity, AmmError> {
+        let snapshot = *self;
+        self.mint_fee()?;
+        match self
+            .pool
+            .remove_liquidity(lp_value, amount_a_min, amount_b_min)
+        {
+            Ok(removed) => {
+                self.update_k_last();
+                Ok(removed)
+            }
+            Err(e) => {
+                *self = snapshot;
+                Err(e)
+            }
+        }
+    }
+
+    /// `(x, y)` the treasury LP would withdraw at the current reserves
+    pub fn treasury_value(&self) -> Result<(u64, u64), MathError> {
+        let (x, y, lp_supply) = self.pool.reserves();
+        let pending = self.pending_protocol_lp()?;
+        let treasury = self
+            .treasury_lp
+            .checked_add(pending)
+            .ok_or(MathError::Overflow)?;
+        if treasury == 0 {
+            return Ok((0, 0));
+        }
+        let lp_supply = to_u64(lp_supply as u128 + pending as u128)?;
+        amm_math::withdraw_liquidity(x, y, treasury, lp_supply)
+    }
+}
@@ -0,0 +1,169 @@
+//! Pre-flight analysis of the first deposit of a pool.
+//!
+//! `create_pool_` mints `sqrt(x) * sqrt(y)` LP to the creator and locks nothing.
+//! A creator depositing dust owns every share, so inflating the reserves without
+//! minting LP makes one share expensive enough that a later deposit rounds down to
+//! zero LP and is captured by the existing shares. Uniswap V2 counters this by
+//! burning `MINIMUM_LIQUIDITY` shares on the first deposit, which makes the attacker
+//! pay for the inflation of shares it does not own. `amm.move` declares its own
+//! [`MINIMUM_LIQUIDITY`] but `create_pool_` does not apply it yet.
+//!
+//! [`simulate_inflation_attack`] inflates the reserves with a donation, rebuilding
+//! the pool through [`PoolSimulator::from_reserves`]. `amm.move` has no entry to
+//! donate into the reserves, an attacker has to route the inflation through swap
+//! fees, so the attacker costs reported here are lower bounds of the attack on chain.
+use crate::{
+    amm_math::{self, MathError},
+    isqrt::Sqrt,
+    simulator::{AmmError, PoolSimulator},
+    uq64x64::UQ64x64,
+};
+
+/// `amm::MINIMUM_LIQUIDITY`, declared but not locked by `create_pool_`
+pub const MINIMUM_LIQUIDITY: u64 = 10;
+
+/// Uniswap V2's `MINIMUM_LIQUIDITY`
+pub const UNISWAP_V2_MINIMUM_LIQUIDITY: u64 = 1000;
+
+/// How the first deposit is minted
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LockMode {
+    /// `create_pool_` today, every share goes to the creator
+    None,
+    /// the given number of shares is minted to nobody
+    MinimumLiquidity(u64),
+}
+
+impl LockMode {
+    fn locked(&self) -> u64 {
+        match self {
+            LockMode::None => 0,
+            LockMode::MinimumLiquidity(locked) => *locked,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FirstDepositReport {
+    /// `sqrt(x) * sqrt(y)`
+    pub lp_supply: u64,
+    /// LP received by the creator, `lp_supply` less the locked shares
+    pub lp_minted: u64,
+    pub locked: u64,
+    /// reserve of X behind one share
+    pub share_price_x: UQ64x64,
+    /// reserve of Y behind one share
+    pub share_price_y: UQ64x64,
+    /// X to add to the reserves so that a deposit of `victim_x` mints no LP
+    pub inflation_cost_x: u128,
+    /// part of `inflation_cost_x` owned by the locked shares, lost by the attacker
+    pub locked_cost_x: u128,
+}
+
+/// LP minted by the first deposit of `(x, y)` into a pool charging `fee_percentage` under `lock`
+fn first_mint(
+    x: u64,
+    y: u64,
+    fee_percentage: u64,
+    lock: LockMode,
+) -> Result<(PoolSimulator, u64), AmmError> {
+    let (pool, lp_supply) = PoolSimulator::create(x, y, fee_percentage)?;
+    // Uniswap V2 asserts `liquidity > 0` once the locked shares are taken out
+    let lp_minted = lp_supply
+        .checked_sub(lock.locked())
+        .filter(|lp| *lp > 0)
+        .ok_or(AmmError::Math(MathError::InsufficientLiquidity))?;
+    Ok((pool, lp_minted))
+}
+
+/// Report for creating a pool with `(x, y)` and `fee_percentage`, `victim_x` is the
+/// size of a later deposit to protect
+pub fn analyze(
+    x: u64,
+    y: u64,
+    fee_percentage: u64,
+    lock: LockMode,
+    victim_x: u64,
+) -> Result<FirstDepositReport, AmmError> {
+    let (pool, lp_minted) = first_mint(x, y, fee_percentage, lock)?;
+    let lp_supply = pool.lp_supply();
+    let locked = lock.locked();
+
+    // victim LP = victim_x * L / X is zero once X > victim_x * L
+    let target = victim_x as u128 * lp_supply as u128 + 1;
+    let inflation_cost_x = target.saturating_sub(x as u128);
+
+    Ok(FirstDepositReport {
+        lp_supply,
+        lp_minted,
+        locked,
+        share_price_x: UQ64x64::fraction(x, lp_supply)?,
+        share_price_y: UQ64x64::fraction(y, lp_supply)?,
+        inflation_cost_x,
+        locked_cost_x: inflation_cost_x * locked as u128 / lp_supply as u128,
+    })
+}
+
+/// Outcome of the inflation attack against a victim deposit
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AttackOutcome {
+    /// first deposit and donation of the attacker
+    pub attacker_cost_x: u64,
+    pub attacker_cost_y: u64,
+    /// LP the victim receives for its deposit
+    pub victim_lp: u64,
+    /// what the attacker withdraws with all of its shares after the victim deposited
+    pub attacker_withdraw_x: u64,
+    pub attacker_withdraw_y: u64,
+}
+
+impl AttackOutcome {
+    /// `(x, y)` gained by the attacker, negative when the attack costs more than it captures
+    pub fn profit(&self) -> (i128, i128) {
+        (
+            self.attacker_withdraw_x as i128 - self.attacker_cost_x as i128,
+            self.attacker_withdraw_y as i128 - self.attacker_cost_y as i128,
+        )
+    }
+}
+
+/// Smallest first deposit `(s, s)` minting more than `locked` shares
+fn dust_deposit(locked: u64) -> Result<u64, MathError> {
+    let s = locked.sqrt_floor() + 1;
+    s.checked_mul(s).ok_or(MathError::Overflow)
+}
+
+/// Replay the attack on a pool charging `fee_percentage`: dust first deposit, donate
+/// until a deposit of `(victim_x, victim_y)` rounds to zero LP, let the victim
+/// deposit, then withdraw
+///
+/// The victim mints `min(x * L / X, y * L / Y)`, so inflating X alone is enough. X is
+/// the side that works: with Y inflated instead, `add_liquidity_` takes the `opt_a`
+/// branch, splits `opt_b` out of the X coin and aborts rather than minting zero LP.
+pub fn simulate_inflation_attack(
+    victim_x: u64,
+    victim_y: u64,
+    fee_percentage: u64,
+    lock: LockMode,
+) -> Result<AttackOutcome, AmmError> {
+    let dust = dust_deposit(lock.locked())?;
+    let (pool, attacker_lp) = first_mint(dust, dust, fee_percentage, lock)?;
+    let lp_supply = pool.lp_supply();
+
+    let inflated_x = amm_math::to_u64(victim_x as u128 * lp_supply as u128 + 1)?.max(dust);
+    let inflated_y = dust;
+    // the donation, see the module doc
+    let mut pool =
+        PoolSimulator::from_reserves(inflated_x, inflated_y, lp_supply, pool.fee_percentage());
+
+    let victim = pool.add_liquidity(victim_x, victim_y, 0, 0)?;
+    let withdrawn = pool.remove_liquidity(attacker_lp, 0, 0)?;
+
+    Ok(AttackOutcome {
+        attacker_cost_x: inflated_x,
+        attacker_cost_y: inflated_y,
+        victim_lp: victim.lp_output,
+        attacker_withdraw_x: withdrawn.amount_x,
+        attacker_withdraw_y: withdrawn.amount_y,
+    })
+}
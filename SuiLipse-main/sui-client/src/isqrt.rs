@@ -0,0 +1,141 @@
+//! Integer square roots for `u64`, `u128` and `U256`.
+//!
+//! Every root is computed digit by digit, so the floor is exact for the whole
+//! domain; `ceil` and `nearest` are derived from the floor and its remainder.
+//! The root keeps the type of its input, `u128::MAX.sqrt_ceil()` is `2^64`.
+use crate::amm_math::Rounding;
+use ethnum::U256;
+
+pub trait Sqrt: Sized {
+    /// largest `r` with `r * r <= self`
+    fn sqrt_floor(self) -> Self;
+
+    /// smallest `r` with `r * r >= self`
+    fn sqrt_ceil(self) -> Self;
+
+    /// closest integer to the real root, `r` when `self <= r * r + r`
+    fn sqrt_nearest(self) -> Self;
+
+    fn sqrt_rounded(self, rounding: Rounding) -> Self {
+        match rounding {
+            Rounding::Floor => self.sqrt_floor(),
+            Rounding::Ceil => self.sqrt_ceil(),
+            Rounding::Nearest => self.sqrt_nearest(),
+        }
+    }
+}
+
+impl Sqrt for u128 {
+    fn sqrt_floor(self) -> Self {
+        // highest power of four not above the input
+        let mut bit = if self == 0 {
+            0
+        } else {
+            1u128 << ((127 - self.leading_zeros()) & !1)
+        };
+        let mut x = self;
+        let mut res = 0u128;
+        while bit != 0 {
+            if x >= res + bit {
+                x -= res + bit;
+                res = (res >> 1) + bit;
+            } else {
+                res >>= 1;
+            }
+            bit >>= 2;
+        }
+        res
+    }
+
+    fn sqrt_ceil(self) -> Self {
+        let r = self.sqrt_floor();
+        if r * r == self {
+            r
+        } else {
+            r + 1
+        }
+    }
+
+    fn sqrt_nearest(self) -> Self {
+        let r = self.sqrt_floor();
+        // (r + 1/2)^2 = r^2 + r + 1/4, the remainder is at most 2r
+        if self - r * r > r {
+            r + 1
+        } else {
+            r
+        }
+    }
+}
+
+impl Sqrt for u64 {
+    fn sqrt_floor(self) -> Self {
+        (self as u128).sqrt_floor() as u64
+    }
+
+    fn sqrt_ceil(self) -> Self {
+        (self as u128).sqrt_ceil() as u64
+    }
+
+    fn sqrt_nearest(self) -> Self {
+        (self as u128).sqrt_nearest() as u64
+    }
+}
+
+impl Sqrt for U256 {
+    fn sqrt_floor(self) -> Self {
+        let mut bit = if self == 0 {
+            U256::ZERO
+        } else {
+            U256::ONE << ((255 - self.leading_zeros()) & !1)
+        };
+        let mut x = self;
+        let mut res = U256::ZERO;
+        while bit != 0 {
+            if x >= res + bit {
+                x -= res + bit;
+                res = (res >> 1u32) + bit;
+            } else {
+                res >>= 1u32;
+            }
+            bit >>= 2u32;
+        }
+        res
+    }
+
+    fn sqrt_ceil(self) -> Self {
+        let r = self.sqrt_floor();
+        if r * r == self {
+            r
+        } else {
+            r + 1
+        }
+    }
+
+    fn sqrt_nearest(self) -> Self {
+        let r = self.sqrt_floor();
+        if self - r * r > r {
+            r + 1
+        } else {
+            r
+        }
+    }
+}
+
+/// How the LP shares of the first deposit are derived from the reserves
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LiquidityMode {
+    /// `sqrt(x) * sqrt(y)`, bit-exact with `amm_math::get_l` on chain
+    Move,
+    /// `sqrt(x * y)` of the full product
+    Exact(Rounding),
+}
+
+/// LP shares minted by `create_pool` for reserves `(x, y)` under the given mode
+pub fn get_l(x: u64, y: u64, mode: LiquidityMode) -> u64 {
+    match mode {
+        // both roots are below 2^32, the product always fits
+        LiquidityMode::Move => x.sqrt_floor() * y.sqrt_floor(),
+        // sqrt(x * y) <= max(x, y), even rounded up
+        LiquidityMode::Exact(rounding) => (x as u128 * y as u128).sqrt_rounded(rounding) as u64,
+    }
+}
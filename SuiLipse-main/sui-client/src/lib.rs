@@ -1,42 +1,37 @@
 #![allow(unused)]
 use dirs;
 use std::path::PathBuf;
+pub mod amm_math;
+pub mod arbitrage;
+pub mod client;
+pub mod concentrated;
+pub mod curve;
+pub mod events;
+pub mod fees;
+pub mod first_deposit;
+pub mod isqrt;
+pub mod oracle;
+pub mod position;
+pub mod report;
+pub mod router;
+mod serde_str;
+pub mod simulator;
 pub mod state;
+pub mod type_tag;
+pub mod uq128x128;
+pub mod uq64x64;
+pub mod zap;
 
+use amm_math::{MathError, FEE_SCALING};
+
+/// floor square root, `amm_math::sqrt` on chain
 pub fn sqrt(y: u64) -> u64 {
-    if (y < 4) {
-        if (y == 0) {
-            0u64
-        } else {
-            1u64
-        }
-    } else {
-        let mut z = y;
-        let mut x = y / 2 + 1;
-        while (x < z) {
-            z = x;
-            x = (y / x + x) / 2;
-        }
-        z
-    }
+    isqrt::Sqrt::sqrt_floor(y)
 }
 
+#[deprecated(note = "use `isqrt::Sqrt::sqrt_floor`, the result is the same")]
 pub fn sui_sqrt(x: u64) -> u64 {
-    let mut bit = 1u128 << 64;
-    let mut res = 0u128;
-    let mut x = (x as u128);
-
-    while (bit != 0) {
-        if (x >= res + bit) {
-            x = x - (res + bit);
-            res = (res >> 1) + bit;
-        } else {
-            res = res >> 1;
-        };
-        bit = bit >> 2;
-    }
-
-    (res as u64)
+    isqrt::Sqrt::sqrt_floor(x)
 }
 
 pub fn min(a: u64, b: u64) -> u64 {
@@ -55,27 +50,36 @@ pub fn max(a: u64, b: u64) -> u64 {
     }
 }
 
-pub fn quote(reserve_a: u64, reserve_b: u64, input_a: u64) -> u64 {
-    (reserve_b / reserve_a) * input_a
+pub fn quote(reserve_a: u64, reserve_b: u64, input_a: u64) -> Result<u64, MathError> {
+    amm_math::quote(reserve_a, reserve_b, input_a)
 }
 
-pub fn get_input(dx: u64, x: u64, y: u64, f: u64) -> u64 {
-    let dx_fee_deduction = (10000 - f) * dx;
-    let numerator = dx_fee_deduction * y;
-    let denominator = 10000 * x + dx_fee_deduction;
+pub fn get_input(dx: u64, x: u64, y: u64, f: u64) -> Result<u64, MathError> {
+    amm_math::get_output(dx, x, y, f, FEE_SCALING)
+}
 
-    (numerator / denominator)
+/// minimum `dx` to receive `dy`, inverse of `get_input`
+pub fn get_input_for_output(dy: u64, x: u64, y: u64, f: u64) -> Result<u64, MathError> {
+    amm_math::get_amount_in(dy, x, y, f, FEE_SCALING)
 }
 
-pub fn minted_lp_after_increase_liquidity(x: u64, y: u64, dx: u64, dy: u64, lp_supply: u64) -> u64 {
-    min(dx * lp_supply / x, dy * lp_supply / y)
+pub fn minted_lp_after_increase_liquidity(
+    x: u64,
+    y: u64,
+    dx: u64,
+    dy: u64,
+    lp_supply: u64,
+) -> Result<u64, MathError> {
+    amm_math::minted_lp(x, y, dx, dy, lp_supply)
 }
 
-pub fn withdraw_liquidity(sui_r: u64, token_y_r: u64, lp_value: u64, lp_supply: u64) -> (u64, u64) {
-    (
-        (sui_r * lp_value / lp_supply),
-        (token_y_r * lp_value / lp_supply),
-    )
+pub fn withdraw_liquidity(
+    sui_r: u64,
+    token_y_r: u64,
+    lp_value: u64,
+    lp_supply: u64,
+) -> Result<(u64, u64), MathError> {
+    amm_math::withdraw_liquidity(sui_r, token_y_r, lp_value, lp_supply)
 }
 
 pub fn default_keystore_path() -> PathBuf {
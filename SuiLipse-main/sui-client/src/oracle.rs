@@ -0,0 +1,107 @@
+//! TWAP reader over the cumulative prices kept by `Pool<V, X, Y>`.
+//!
+//! `update_cumulative_price` accumulates `UQ64x64(reserve_y / reserve_x) * time_elapsed`
+//! into `last_price_x_cumulative` (and the inverse into `last_price_y_cumulative`)
+//! with `amm_math::overflow_add`, so the difference of two observations divided by
+//! the elapsed time is the time-weighted average price over that window. Like
+//! Uniswap V2 the accumulators are allowed to wrap, the difference is taken modulo 2^128.
+use crate::{
+    amm_math::{self, MathError},
+    uq64x64::UQ64x64,
+};
+use thiserror::Error;
+
+pub use crate::events::OracleUpdatedEvent;
+
+#[derive(Error, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OracleError {
+    /// the later observation is not strictly newer than the earlier one
+    #[error("observations at {0} and {1} do not span any time")]
+    EmptyWindow(u64, u64),
+    #[error(transparent)]
+    Math(#[from] MathError),
+}
+
+/// Cumulative prices of a pool at a given timestamp
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Observation {
+    pub timestamp: u64,
+    pub price_x_cumulative: u128,
+    pub price_y_cumulative: u128,
+}
+
+/// Time-weighted average prices between two observations
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Twap {
+    /// price of X quoted in Y
+    pub price_x: UQ64x64,
+    /// price of Y quoted in X
+    pub price_y: UQ64x64,
+    pub elapsed: u64,
+}
+
+impl Observation {
+    /// the event carries no timestamp, it has to be taken from the emitting transaction
+    pub fn from_event(event: &OracleUpdatedEvent, timestamp: u64) -> Self {
+        Observation {
+            timestamp,
+            price_x_cumulative: event.last_price_cumulative_0,
+            price_y_cumulative: event.last_price_cumulative_1,
+        }
+    }
+
+    /// mirror of `update_cumulative_price`, the observation the pool would record at `block_timestamp`
+    pub fn update(
+        &self,
+        reserve_x: u64,
+        reserve_y: u64,
+        block_timestamp: u64,
+    ) -> Result<Self, OracleError> {
+        // the Move code aborts on the subtraction when time goes backwards
+        let time_elapsed = block_timestamp
+            .checked_sub(self.timestamp)
+            .ok_or(OracleError::EmptyWindow(self.timestamp, block_timestamp))?
+            as u128;
+        let mut next = Observation {
+            timestamp: block_timestamp,
+            ..*self
+        };
+
+        if time_elapsed > 0 && reserve_x != 0 && reserve_y != 0 {
+            let price_x = UQ64x64::fraction(reserve_y, reserve_x)?.to_u128();
+            let price_y = UQ64x64::fraction(reserve_x, reserve_y)?.to_u128();
+            let price_x = price_x
+                .checked_mul(time_elapsed)
+                .ok_or(MathError::Overflow)?;
+            let price_y = price_y
+                .checked_mul(time_elapsed)
+                .ok_or(MathError::Overflow)?;
+
+            next.price_x_cumulative = amm_math::overflow_add(self.price_x_cumulative, price_x);
+            next.price_y_cumulative = amm_math::overflow_add(self.price_y_cumulative, price_y);
+        }
+
+        Ok(next)
+    }
+}
+
+/// Average prices over `[earlier.timestamp, later.timestamp]`
+pub fn twap(earlier: &Observation, later: &Observation) -> Result<Twap, OracleError> {
+    if later.timestamp <= earlier.timestamp {
+        return Err(OracleError::EmptyWindow(earlier.timestamp, later.timestamp));
+    }
+    let elapsed = later.timestamp - earlier.timestamp;
+
+    let delta_x = later
+        .price_x_cumulative
+        .wrapping_sub(earlier.price_x_cumulative);
+    let delta_y = later
+        .price_y_cumulative
+        .wrapping_sub(earlier.price_y_cumulative);
+
+    Ok(Twap {
+        price_x: UQ64x64::from_raw(delta_x / elapsed as u128),
+        price_y: UQ64x64::from_raw(delta_y / elapsed as u128),
+        elapsed,
+    })
+}
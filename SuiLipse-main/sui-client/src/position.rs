@@ -0,0 +1,122 @@
+//! Valuation of an LP position built on `withdraw_liquidity`.
+//!
+//! Swap fees stay in the reserves, so they show up as growth of `sqrt(x * y)` per
+//! LP share. Between the entry and now the position is split into the part a
+//! fee-less pool would hold, which carries the impermanent loss, and the accrued fees.
+use crate::{
+    amm_math::{self, MathError},
+    isqrt::Sqrt,
+    simulator::PoolSimulator,
+};
+use ethnum::U256;
+
+/// Position at the time it was opened
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EntrySnapshot {
+    pub pool: PoolSimulator,
+    /// tokens deposited, the benchmark of holding instead of providing liquidity
+    pub amount_x: u64,
+    pub amount_y: u64,
+}
+
+impl EntrySnapshot {
+    /// Entry of `lp_balance` shares at `pool`, deposits taken as their underlying amounts at that time
+    pub fn new(pool: PoolSimulator, lp_balance: u64) -> Result<Self, MathError> {
+        let (reserve_x, reserve_y, lp_supply) = pool.reserves();
+        let (amount_x, amount_y) =
+            amm_math::withdraw_liquidity(reserve_x, reserve_y, lp_balance, lp_supply)?;
+
+        Ok(EntrySnapshot {
+            pool,
+            amount_x,
+            amount_y,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PositionReport {
+    pub lp_balance: u64,
+    /// what `remove_liquidity` would return now
+    pub amount_x: u64,
+    pub amount_y: u64,
+    /// underlying amounts valued at the spot price, in X
+    pub value_in_x: u128,
+    /// underlying amounts valued at the spot price, in Y
+    pub value_in_y: u128,
+    /// entry deposits valued at the current spot price, in X
+    pub hold_value_in_x: u128,
+    /// fee-less position against holding, `-0.05` is a 5% loss
+    pub impermanent_loss: f64,
+    /// part of the underlying amounts earned by swap fees since the entry
+    pub fee_x: u64,
+    pub fee_y: u64,
+}
+
+/// `amount_x + amount_y * reserve_x / reserve_y`
+fn value_in_x(amount_x: u64, amount_y: u64, reserve_x: u64, reserve_y: u64) -> u128 {
+    amount_x as u128 + amount_y as u128 * reserve_x as u128 / reserve_y as u128
+}
+
+/// Analytic impermanent loss of a constant product pool after the price moved by `price_ratio`
+/// IL = 2 * sqrt(r) / (1 + r) - 1
+pub fn impermanent_loss(price_ratio: f64) -> f64 {
+    2f64 * price_ratio.sqrt() / (1f64 + price_ratio) - 1f64
+}
+
+/// Value `lp_balance` shares of `pool` against the position opened at `entry`
+pub fn analyze(
+    lp_balance: u64,
+    pool: &PoolSimulator,
+    entry: &EntrySnapshot,
+) -> Result<PositionReport, MathError> {
+    let (reserve_x, reserve_y, lp_supply) = pool.reserves();
+    let (amount_x, amount_y) =
+        amm_math::withdraw_liquidity(reserve_x, reserve_y, lp_balance, lp_supply)?;
+
+    let (entry_x, entry_y, entry_supply) = entry.pool.reserves();
+    if entry_x == 0 || entry_y == 0 {
+        return Err(MathError::ReservesEmpty);
+    }
+    if entry_supply == 0 {
+        return Err(MathError::DivideByZero);
+    }
+
+    // sqrt(k) per share grows only by fees: g0 / g1 = sqrt(k0) * L1 / (L0 * sqrt(k1))
+    let root_k0 = U256::from(entry_x as u128 * entry_y as u128).sqrt_floor();
+    let root_k1 = U256::from(reserve_x as u128 * reserve_y as u128).sqrt_floor();
+    let num = root_k0 * U256::from(lp_supply);
+    let den = root_k1 * U256::from(entry_supply);
+    // rounding of withdrawals can shrink `sqrt(k)` per share, no fee was earned then
+    let fee_of = |amount: u64| -> Result<u64, MathError> {
+        if num >= den {
+            return Ok(0);
+        }
+        let feeless = U256::from(amount) * num / den;
+        amm_math::u256_to_u64(U256::from(amount) - feeless)
+    };
+    let fee_x = fee_of(amount_x)?;
+    let fee_y = fee_of(amount_y)?;
+
+    let value_x = value_in_x(amount_x, amount_y, reserve_x, reserve_y);
+    let value_y = value_in_x(amount_y, amount_x, reserve_y, reserve_x);
+    let hold_value_in_x = value_in_x(entry.amount_x, entry.amount_y, reserve_x, reserve_y);
+    let feeless_value_in_x = value_in_x(amount_x - fee_x, amount_y - fee_y, reserve_x, reserve_y);
+    let impermanent_loss = if hold_value_in_x == 0 {
+        0f64
+    } else {
+        feeless_value_in_x as f64 / hold_value_in_x as f64 - 1f64
+    };
+
+    Ok(PositionReport {
+        lp_balance,
+        amount_x,
+        amount_y,
+        value_in_x: value_x,
+        value_in_y: value_y,
+        hold_value_in_x,
+        impermanent_loss,
+        fee_x,
+        fee_y,
+    })
+}
@@ -0,0 +1,128 @@
+//! Human facing summary of a swap quote, shown before a swap is signed.
+//!
+//! Prices are quoted as output token per input token, the same orientation as
+//! `UQ64x64::fraction(reserve_out, reserve_in)`.
+use crate::{
+    amm_math::{self, u256_to_u64, MathError, FEE_SCALING},
+    uq64x64::UQ64x64,
+};
+use ethnum::U256;
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct QuoteReport {
+    pub amount_in: u64,
+    pub amount_out: u64,
+    /// `reserve_out / reserve_in` before the swap
+    pub spot_price_before: UQ64x64,
+    /// `reserve_out / reserve_in` after the swap, the whole input including the fee stays in the pool
+    pub spot_price_after: UQ64x64,
+    /// `amount_out / amount_in`
+    pub effective_price: UQ64x64,
+    /// shortfall of the output against the fee-deducted input at spot price, rounded up
+    pub price_impact_bps: u64,
+    /// `amount_in * fee_percentage / FEE_SCALING`, in input token
+    pub fee_paid: u64,
+    pub slippage: u64,
+    /// `amount_out` reduced by `slippage` basis points, rounded down
+    pub min_received: u64,
+}
+
+/// lowest output still accepted after the price moved by `slippage` basis points
+pub fn min_amount_out(amount_out: u64, slippage: u64) -> Result<u64, MathError> {
+    if slippage > FEE_SCALING {
+        return Err(MathError::InvalidFee);
+    }
+    amm_math::to_u64(amount_out as u128 * (FEE_SCALING - slippage) as u128 / FEE_SCALING as u128)
+}
+
+impl QuoteReport {
+    /// Report for swapping `amount_in` against `(reserve_in, reserve_out)`, as `get_input` does
+    pub fn new(
+        amount_in: u64,
+        reserve_in: u64,
+        reserve_out: u64,
+        f: u64,
+        slippage: u64,
+    ) -> Result<Self, MathError> {
+        let amount_out = amm_math::get_output(amount_in, reserve_in, reserve_out, f, FEE_SCALING)?;
+        Self::from_amounts(amount_in, amount_out, reserve_in, reserve_out, f, slippage)
+    }
+
+    /// Report for an exact output swap, `amount_in` is the `get_amount_in` ceiling
+    pub fn exact_output(
+        amount_out: u64,
+        reserve_in: u64,
+        reserve_out: u64,
+        f: u64,
+        slippage: u64,
+    ) -> Result<Self, MathError> {
+        let amount_in =
+            amm_math::get_amount_in(amount_out, reserve_in, reserve_out, f, FEE_SCALING)?;
+        Self::from_amounts(amount_in, amount_out, reserve_in, reserve_out, f, slippage)
+    }
+
+    fn from_amounts(
+        amount_in: u64,
+        amount_out: u64,
+        reserve_in: u64,
+        reserve_out: u64,
+        f: u64,
+        slippage: u64,
+    ) -> Result<Self, MathError> {
+        let reserve_in_after = reserve_in
+            .checked_add(amount_in)
+            .ok_or(MathError::Overflow)?;
+        let reserve_out_after = reserve_out
+            .checked_sub(amount_out)
+            .ok_or(MathError::InsufficientLiquidity)?;
+
+        // ideal = amount_in * (S - f) / S * reserve_out / reserve_in
+        // impact = 1 - amount_out / ideal
+        let ideal =
+            U256::from((FEE_SCALING - f) as u128 * amount_in as u128) * U256::from(reserve_out);
+        let actual = U256::from(FEE_SCALING as u128 * amount_out as u128) * U256::from(reserve_in);
+        let price_impact_bps = if ideal == 0 || actual >= ideal {
+            0
+        } else {
+            let shortfall = (ideal - actual) * U256::from(FEE_SCALING);
+            u256_to_u64((shortfall + ideal - 1) / ideal)?
+        };
+
+        Ok(QuoteReport {
+            amount_in,
+            amount_out,
+            spot_price_before: UQ64x64::fraction(reserve_out, reserve_in)?,
+            spot_price_after: UQ64x64::fraction(reserve_out_after, reserve_in_after)?,
+            effective_price: UQ64x64::fraction(amount_out, amount_in)?,
+            price_impact_bps,
+            fee_paid: amm_math::to_u64(amount_in as u128 * f as u128 / FEE_SCALING as u128)?,
+            slippage,
+            min_received: min_amount_out(amount_out, slippage)?,
+        })
+    }
+}
+
+impl fmt::Display for QuoteReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "amount in:         {}", self.amount_in)?;
+        writeln!(f, "amount out:        {}", self.amount_out)?;
+        writeln!(f, "spot price:        {:.6}", self.spot_price_before)?;
+        writeln!(f, "price after swap:  {:.6}", self.spot_price_after)?;
+        writeln!(f, "effective price:   {:.6}", self.effective_price)?;
+        writeln!(
+            f,
+            "price impact:      {}.{:02}%",
+            self.price_impact_bps / 100,
+            self.price_impact_bps % 100
+        )?;
+        writeln!(f, "fee paid:          {}", self.fee_paid)?;
+        write!(
+            f,
+            "minimum received:  {} ({}.{:02}% slippage)",
+            self.min_received,
+            self.slippage / 100,
+            self.slippage % 100
+        )
+    }
+}
@@ -0,0 +1,308 @@
+//! Multi-hop routing over a set of known `AMM_V2` pools.
+//!
+//! Tokens are identified by their coin type, e.g. `0x2::sui::SUI`, since that is
+//! what the `X` and `Y` type arguments of `Pool<V, X, Y>` carry. Every simple path
+//! of at most `max_hops` pools is priced with [`amm_math`] and the best one wins.
+use crate::amm_math::{self, MathError, FEE_SCALING};
+use crate::state::Pool;
+use sui_sdk::types::base_types::ObjectID;
+use thiserror::Error;
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum RouterError {
+    #[error("no route from {0} to {1}")]
+    NoRoute(String, String),
+    /// a path index past the pools of the router
+    #[error("no pool at index {0}")]
+    UnknownPool(usize),
+    /// a path entering a pool with a token it does not hold
+    #[error("pool {pool} does not hold {token}")]
+    TokenNotInPool { token: String, pool: ObjectID },
+    #[error(transparent)]
+    Math(#[from] MathError),
+}
+
+/// Edge of the routing graph
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RoutePool {
+    pub id: ObjectID,
+    pub token_x: String,
+    pub token_y: String,
+    pub reserve_x: u64,
+    pub reserve_y: u64,
+    pub fee_percentage: u64,
+}
+
+impl RoutePool {
+    /// `token_x` and `token_y` are the coin types of the pool's `X` and `Y` type arguments
+    pub fn from_state<V, X, Y>(
+        pool: &Pool<V, X, Y>,
+        token_x: impl Into<String>,
+        token_y: impl Into<String>,
+    ) -> Self {
+        let (reserve_x, reserve_y, _) = pool.reserves();
+        RoutePool {
+            id: pool.id(),
+            token_x: token_x.into(),
+            token_y: token_y.into(),
+            reserve_x,
+            reserve_y,
+            fee_percentage: pool.fee_percentage(),
+        }
+    }
+
+    /// `(reserve_in, reserve_out, x_to_y)` when trading `token_in`, `None` if the pool does not hold it
+    fn side(&self, token_in: &str) -> Option<(u64, u64, bool)> {
+        if self.token_x == token_in {
+            Some((self.reserve_x, self.reserve_y, true))
+        } else if self.token_y == token_in {
+            Some((self.reserve_y, self.reserve_x, false))
+        } else {
+            None
+        }
+    }
+
+    /// [`RoutePool::side`], `TokenNotInPool` if the pool does not hold `token_in`
+    fn checked_side(&self, token_in: &str) -> Result<(u64, u64, bool), RouterError> {
+        self.side(token_in)
+            .ok_or_else(|| RouterError::TokenNotInPool {
+                token: token_in.to_string(),
+                pool: self.id,
+            })
+    }
+
+    fn other(&self, token: &str) -> &str {
+        if self.token_x == token {
+            &self.token_y
+        } else {
+            &self.token_x
+        }
+    }
+}
+
+/// One swap of a route, `x_to_y` selects `swap_token_x` over `swap_token_y`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Hop {
+    pub pool: ObjectID,
+    pub token_in: String,
+    pub token_out: String,
+    pub x_to_y: bool,
+    pub amount_in: u64,
+    pub amount_out: u64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Route {
+    pub hops: Vec<Hop>,
+    pub amount_in: u64,
+    pub amount_out: u64,
+    /// shortfall against trading the whole amount at the spot prices, fees included, in basis points
+    pub price_impact_bps: f64,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Router {
+    pools: Vec<RoutePool>,
+}
+
+impl Router {
+    pub fn new(pools: Vec<RoutePool>) -> Self {
+        Router { pools }
+    }
+
+    pub fn add_pool(&mut self, pool: RoutePool) {
+        self.pools.push(pool);
+    }
+
+    pub fn pools(&self) -> &[RoutePool] {
+        &self.pools
+    }
+
+    /// Route receiving the most `token_out` for exactly `amount_in`
+    pub fn best_exact_in(
+        &self,
+        token_in: &str,
+        token_out: &str,
+        amount_in: u64,
+        max_hops: usize,
+    ) -> Result<Route, RouterError> {
+        self.paths(token_in, token_out, max_hops)
+            .into_iter()
+            .filter_map(|path| self.quote_exact_in(token_in, &path, amount_in).ok())
+            .max_by_key(|route| route.amount_out)
+            .ok_or_else(|| RouterError::NoRoute(token_in.to_string(), token_out.to_string()))
+    }
+
+    /// Route paying the least `token_in` for exactly `amount_out`
+    pub fn best_exact_out(
+        &self,
+        token_in: &str,
+        token_out: &str,
+        amount_out: u64,
+        max_hops: usize,
+    ) -> Result<Route, RouterError> {
+        self.paths(token_in, token_out, max_hops)
+            .into_iter()
+            .filter_map(|path| self.quote_exact_out(token_in, &path, amount_out).ok())
+            .min_by_key(|route| route.amount_in)
+            .ok_or_else(|| RouterError::NoRoute(token_in.to_string(), token_out.to_string()))
+    }
+
+    /// Price `amount_in` along the given pool indices
+    pub fn quote_exact_in(
+        &self,
+        token_in: &str,
+        path: &[usize],
+        amount_in: u64,
+    ) -> Result<Route, RouterError> {
+        let mut hops = Vec::with_capacity(path.len());
+        let mut token = token_in;
+        let mut amount = amount_in;
+        for &idx in path {
+            let pool = self.pool(idx)?;
+            let (reserve_in, reserve_out, x_to_y) = pool.checked_side(token)?;
+            let out = amm_math::get_output(
+                amount,
+                reserve_in,
+                reserve_out,
+                pool.fee_percentage,
+                FEE_SCALING,
+            )?;
+            hops.push(Hop {
+                pool: pool.id,
+                token_in: token.to_string(),
+                token_out: pool.other(token).to_string(),
+                x_to_y,
+                amount_in: amount,
+                amount_out: out,
+            });
+            token = pool.other(token);
+            amount = out;
+        }
+        self.route(path, hops)
+    }
+
+    /// Required input for `amount_out` along the given pool indices, walking the path backwards
+    pub fn quote_exact_out(
+        &self,
+        token_in: &str,
+        path: &[usize],
+        amount_out: u64,
+    ) -> Result<Route, RouterError> {
+        // tokens entering each hop
+        let mut tokens = vec![token_in];
+        let mut token = token_in;
+        for &idx in path {
+            let pool = self.pool(idx)?;
+            pool.checked_side(token)?;
+            token = pool.other(token);
+            tokens.push(token);
+        }
+
+        let mut hops = Vec::with_capacity(path.len());
+        let mut amount = amount_out;
+        for (i, &idx) in path.iter().enumerate().rev() {
+            let pool = self.pool(idx)?;
+            let (reserve_in, reserve_out, x_to_y) = pool.checked_side(tokens[i])?;
+            let input = amm_math::get_amount_in(
+                amount,
+                reserve_in,
+                reserve_out,
+                pool.fee_percentage,
+                FEE_SCALING,
+            )?;
+            hops.push(Hop {
+                pool: pool.id,
+                token_in: tokens[i].to_string(),
+                token_out: tokens[i + 1].to_string(),
+                x_to_y,
+                amount_in: input,
+                amount_out: amount,
+            });
+            amount = input;
+        }
+        hops.reverse();
+        self.route(path, hops)
+    }
+
+    fn pool(&self, idx: usize) -> Result<&RoutePool, RouterError> {
+        self.pools.get(idx).ok_or(RouterError::UnknownPool(idx))
+    }
+
+    fn route(&self, path: &[usize], hops: Vec<Hop>) -> Result<Route, RouterError> {
+        let amount_in = hops.first().map(|h| h.amount_in).unwrap_or(0);
+        let amount_out = hops.last().map(|h| h.amount_out).unwrap_or(0);
+
+        let mut spot = 1f64;
+        for (&idx, hop) in path.iter().zip(&hops) {
+            let (reserve_in, reserve_out, _) = self.pool(idx)?.checked_side(&hop.token_in)?;
+            spot *= reserve_out as f64 / reserve_in as f64;
+        }
+        let ideal = amount_in as f64 * spot;
+        let price_impact_bps = if ideal > 0f64 {
+            (1f64 - amount_out as f64 / ideal) * FEE_SCALING as f64
+        } else {
+            0f64
+        };
+
+        Ok(Route {
+            hops,
+            amount_in,
+            amount_out,
+            price_impact_bps,
+        })
+    }
+
+    /// Every simple path of pool indices from `token_in` to `token_out`, no token is visited twice
+    /// and no pool is used twice, with `token_in == token_out` these are the cycles through `token_in`
+    pub(crate) fn paths(
+        &self,
+        token_in: &str,
+        token_out: &str,
+        max_hops: usize,
+    ) -> Vec<Vec<usize>> {
+        let mut paths = Vec::new();
+        let mut path = Vec::new();
+        let mut visited = vec![token_in.to_string()];
+        self.walk(
+            token_in,
+            token_out,
+            max_hops,
+            &mut path,
+            &mut visited,
+            &mut paths,
+        );
+        paths
+    }
+
+    fn walk(
+        &self,
+        token: &str,
+        token_out: &str,
+        max_hops: usize,
+        path: &mut Vec<usize>,
+        visited: &mut Vec<String>,
+        paths: &mut Vec<Vec<usize>>,
+    ) {
+        if path.len() == max_hops {
+            return;
+        }
+        for (idx, pool) in self.pools.iter().enumerate() {
+            if pool.side(token).is_none() || path.contains(&idx) {
+                continue;
+            }
+            let next = pool.other(token);
+            if next == token_out {
+                path.push(idx);
+                paths.push(path.clone());
+                path.pop();
+            } else if !visited.iter().any(|t| t == next) {
+                path.push(idx);
+                visited.push(next.to_string());
+                self.walk(next, token_out, max_hops, path, visited, paths);
+                visited.pop();
+                path.pop();
+            }
+        }
+    }
+}
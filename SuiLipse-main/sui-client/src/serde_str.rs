@@ -0,0 +1,73 @@
+//! Wide Move integers (`u128`, `u256`) are rendered as decimal strings by the JSON RPC,
+//! these helpers read them back from either a string or a plain number.
+//! Binary formats keep the native little-endian layout.
+use ethnum::U256;
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use std::{fmt, marker::PhantomData, str::FromStr};
+
+struct DecimalVisitor<T>(PhantomData<T>);
+
+impl<'de, T> de::Visitor<'de> for DecimalVisitor<T>
+where
+    T: FromStr + From<u64> + From<u128>,
+    T::Err: fmt::Display,
+{
+    type Value = T;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("an unsigned integer or a decimal string")
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<T, E> {
+        v.parse().map_err(E::custom)
+    }
+
+    fn visit_u64<E: de::Error>(self, v: u64) -> Result<T, E> {
+        Ok(T::from(v))
+    }
+
+    fn visit_u128<E: de::Error>(self, v: u128) -> Result<T, E> {
+        Ok(T::from(v))
+    }
+}
+
+pub mod u128 {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(v: &u128, serializer: S) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            serializer.collect_str(v)
+        } else {
+            v.serialize(serializer)
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u128, D::Error> {
+        if deserializer.is_human_readable() {
+            deserializer.deserialize_any(DecimalVisitor(PhantomData))
+        } else {
+            // `u128` alone would name this module
+            <core::primitive::u128>::deserialize(deserializer)
+        }
+    }
+}
+
+pub mod u256 {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(v: &U256, serializer: S) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            serializer.collect_str(v)
+        } else {
+            v.to_le_bytes().serialize(serializer)
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<U256, D::Error> {
+        if deserializer.is_human_readable() {
+            deserializer.deserialize_any(DecimalVisitor(PhantomData))
+        } else {
+            <[u8; 32]>::deserialize(deserializer).map(U256::from_le_bytes)
+        }
+    }
+}
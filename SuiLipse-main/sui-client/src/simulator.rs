@@ -0,0 +1,319 @@
+//! Off-chain replay of the state transitions in `sui_lipse::amm`.
+//!
+//! Every method follows the body of its Move counterpart step by step, so a
+//! sequence of calls predicts both the resulting reserves and the point where the
+//! transaction would abort, including the `u64` overflows of `amm_math`. A failed
+//! call leaves the simulator untouched, just like an aborted transaction leaves the
+//! pool untouched.
+use crate::{
+    amm_math::{self, MathError, FEE_SCALING},
+    state::Pool,
+};
+use thiserror::Error;
+
+/// - Max stored value for both tokens is: U64_MAX / 10_000
+pub const MAX_POOL_VALUE: u64 = u64::MAX / 10000;
+
+#[derive(Error, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AmmError {
+    /// `ERR_Zero_Amount`
+    #[error("input amount is zero")]
+    ZeroAmount,
+    /// `ERR_Reserves_Empty`
+    #[error("one of the reserves is empty")]
+    ReservesEmpty,
+    /// `ERR_Invalid_Fee`
+    #[error("fee percentage out of range (0, 10000]")]
+    InvalidFee,
+    /// `ERR_Full_Pool`
+    #[error("pool is over MAX_POOL_VALUE")]
+    FullPool,
+    /// `ERR_Insufficient_A_Amount`
+    #[error("insufficient token x amount")]
+    InsufficientAAmount,
+    /// `ERR_Insufficient_B_Amount`
+    #[error("insufficient token y amount")]
+    InsufficientBAmount,
+    /// `balance::split` or `balance::decrease_supply` takes more than it holds
+    #[error("insufficient balance")]
+    InsufficientBalance,
+    #[error(transparent)]
+    Math(#[from] MathError),
+}
+
+impl AmmError {
+    /// Abort code raised by `amm.move` or `amm_math.move`, `None` for aborts raised by the framework or the VM
+    pub fn abort_code(&self) -> Option<u64> {
+        match self {
+            AmmError::ZeroAmount => Some(0),
+            AmmError::ReservesEmpty => Some(1),
+            AmmError::InvalidFee => Some(2),
+            AmmError::FullPool => Some(3),
+            AmmError::InsufficientAAmount => Some(6),
+            AmmError::InsufficientBAmount => Some(7),
+            AmmError::InsufficientBalance => None,
+            AmmError::Math(e) => e.abort_code(),
+        }
+    }
+}
+
+/// Result of [`PoolSimulator::add_liquidity`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AddLiquidity {
+    pub lp_output: u64,
+    /// `amount_a` reported by `add_liquidity_`
+    pub amount_x: u64,
+    /// `amount_b` reported by `add_liquidity_`
+    pub amount_y: u64,
+    /// coin sent back to the sender
+    pub refund_x: u64,
+    pub refund_y: u64,
+}
+
+/// Result of [`PoolSimulator::remove_liquidity`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RemoveLiquidity {
+    pub amount_x: u64,
+    pub amount_y: u64,
+    pub lp_value: u64,
+}
+
+/// Result of [`PoolSimulator::swap_x`] and [`PoolSimulator::swap_y`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Swap {
+    pub input: u64,
+    pub output: u64,
+}
+
+/// In-memory `Pool<V, X, Y>` reduced to the values the pool logic reads
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PoolSimulator {
+    reserve_x: u64,
+    reserve_y: u64,
+    lp_supply: u64,
+    fee_percentage: u64,
+}
+
+impl PoolSimulator {
+    /// Start from an existing pool snapshot
+    pub fn from_reserves(
+        reserve_x: u64,
+        reserve_y: u64,
+        lp_supply: u64,
+        fee_percentage: u64,
+    ) -> Self {
+        Self {
+            reserve_x,
+            reserve_y,
+            lp_supply,
+            fee_percentage,
+        }
+    }
+
+    /// `create_pool_`, returns the pool and the LP minted to the creator
+    pub fn create(
+        token_x: u64,
+        token_y: u64,
+        fee_percentage: u64,
+    ) -> Result<(Self, u64), AmmError> {
+        if token_x == 0 || token_y == 0 {
+            return Err(AmmError::ZeroAmount);
+        }
+        if token_x >= MAX_POOL_VALUE || token_y >= MAX_POOL_VALUE {
+            return Err(AmmError::FullPool);
+        }
+        if fee_percentage == 0 || fee_percentage > FEE_SCALING {
+            return Err(AmmError::InvalidFee);
+        }
+
+        let lp_shares = amm_math::get_l(token_x, token_y);
+        let pool = Self::from_reserves(token_x, token_y, lp_shares, fee_percentage);
+
+        Ok((pool, lp_shares))
+    }
+
+    /// ( reserve_x, reserve_y, lp_supply ), same as `get_reserves`
+    pub fn reserves(&self) -> (u64, u64, u64) {
+        (self.reserve_x, self.reserve_y, self.lp_supply)
+    }
+
+    pub fn reserve_x(&self) -> u64 {
+        self.reserve_x
+    }
+
+    pub fn reserve_y(&self) -> u64 {
+        self.reserve_y
+    }
+
+    pub fn lp_supply(&self) -> u64 {
+        self.lp_supply
+    }
+
+    pub fn fee_percentage(&self) -> u64 {
+        self.fee_percentage
+    }
+
+    /// `add_liquidity_`
+    pub fn add_liquidity(
+        &mut self,
+        token_x: u64,
+        token_y: u64,
+        amount_x_min: u64,
+        amount_y_min: u64,
+    ) -> Result<AddLiquidity, AmmError> {
+        if token_x == 0 || token_y == 0 {
+            return Err(AmmError::ZeroAmount);
+        }
+        let (token_x_r, token_y_r, lp_supply) = self.reserves();
+
+        // (amount_a, amount_b, deposited x, deposited y)
+        let (amount_a, amount_b, deposit_x, deposit_y) = if token_x_r == 0 && token_y_r == 0 {
+            (token_x, token_y, token_x, token_y)
+        } else {
+            let opt_b = amm_math::quote(token_x_r, token_y_r, token_x)?;
+            if opt_b <= token_y {
+                if opt_b < amount_y_min {
+                    return Err(AmmError::InsufficientBAmount);
+                }
+                (token_x, opt_b, token_x, opt_b)
+            } else {
+                let opt_a = amm_math::quote(token_y_r, token_x_r, token_y)?;
+                if opt_a > token_x || opt_a < amount_x_min {
+                    return Err(AmmError::InsufficientAAmount);
+                }
+                // the Move code splits `opt_b` (not `opt_a`) out of `token_x`
+                if opt_b > token_x {
+                    return Err(AmmError::InsufficientBalance);
+                }
+                (opt_a, token_y, opt_b, token_y)
+            }
+        };
+
+        // a drained pool divides by its empty reserves here
+        if token_x_r == 0 || token_y_r == 0 {
+            return Err(MathError::DivideByZero.into());
+        }
+        let lp_output = amm_math::minted_lp(token_x_r, token_y_r, amount_a, amount_b, lp_supply)?;
+
+        let reserve_x = token_x_r
+            .checked_add(deposit_x)
+            .ok_or(MathError::Overflow)?;
+        let reserve_y = token_y_r
+            .checked_add(deposit_y)
+            .ok_or(MathError::Overflow)?;
+        if reserve_x >= MAX_POOL_VALUE || reserve_y >= MAX_POOL_VALUE {
+            return Err(AmmError::FullPool);
+        }
+        let lp_supply = lp_supply
+            .checked_add(lp_output)
+            .ok_or(MathError::Overflow)?;
+
+        self.reserve_x = reserve_x;
+        self.reserve_y = reserve_y;
+        self.lp_supply = lp_supply;
+
+        Ok(AddLiquidity {
+            lp_output,
+            amount_x: amount_a,
+            amount_y: amount_b,
+            refund_x: token_x - deposit_x,
+            refund_y: token_y - deposit_y,
+        })
+    }
+
+    /// `remove_liquidity_`
+    pub fn remove_liquidity(
+        &mut self,
+        lp_value: u64,
+        amount_a_min: u64,
+        amount_b_min: u64,
+    ) -> Result<RemoveLiquidity, AmmError> {
+        if lp_value == 0 {
+            return Err(AmmError::ZeroAmount);
+        }
+        let (res_x, res_y, lp_s) = self.reserves();
+        let (token_x_output, token_y_output) =
+            amm_math::withdraw_liquidity(res_x, res_y, lp_value, lp_s)?;
+        if token_x_output < amount_a_min {
+            return Err(AmmError::InsufficientAAmount);
+        }
+        if token_y_output < amount_b_min {
+            return Err(AmmError::InsufficientBAmount);
+        }
+        if lp_value > lp_s || token_x_output > res_x || token_y_output > res_y {
+            return Err(AmmError::InsufficientBalance);
+        }
+
+        self.lp_supply = lp_s - lp_value;
+        self.reserve_x = res_x - token_x_output;
+        self.reserve_y = res_y - token_y_output;
+
+        Ok(RemoveLiquidity {
+            amount_x: token_x_output,
+            amount_y: token_y_output,
+            lp_value,
+        })
+    }
+
+    /// `swap_token_x_`, sells X for Y
+    pub fn swap_x(&mut self, token_x: u64) -> Result<Swap, AmmError> {
+        if token_x == 0 {
+            return Err(AmmError::ZeroAmount);
+        }
+        let output = amm_math::get_output(
+            token_x,
+            self.reserve_x,
+            self.reserve_y,
+            self.fee_percentage,
+            FEE_SCALING,
+        )?;
+        let reserve_x = self
+            .reserve_x
+            .checked_add(token_x)
+            .ok_or(MathError::Overflow)?;
+
+        self.reserve_x = reserve_x;
+        self.reserve_y -= output;
+
+        Ok(Swap {
+            input: token_x,
+            output,
+        })
+    }
+
+    /// `swap_token_y_`, sells Y for X
+    pub fn swap_y(&mut self, token_y: u64) -> Result<Swap, AmmError> {
+        if token_y == 0 {
+            return Err(AmmError::ZeroAmount);
+        }
+        if self.reserve_x == 0 || self.reserve_y == 0 {
+            return Err(AmmError::ReservesEmpty);
+        }
+        let output = amm_math::get_output(
+            token_y,
+            self.reserve_y,
+            self.reserve_x,
+            self.fee_percentage,
+            FEE_SCALING,
+        )?;
+        let reserve_y = self
+            .reserve_y
+            .checked_add(token_y)
+            .ok_or(MathError::Overflow)?;
+
+        self.reserve_y = reserve_y;
+        self.reserve_x -= output;
+
+        Ok(Swap {
+            input: token_y,
+            output,
+        })
+    }
+}
+
+impl<V, X, Y> From<&Pool<V, X, Y>> for PoolSimulator {
+    fn from(pool: &Pool<V, X, Y>) -> Self {
+        let (reserve_x, reserve_y, lp_supply) = pool.reserves();
+        Self::from_reserves(reserve_x, reserve_y, lp_supply, pool.fee_percentage())
+    }
+}
@@ -0,0 +1,119 @@
+//! `sui::balance::{Balance, Supply}`, both a single `value: u64` on chain.
+//!
+//! The JSON RPC may render them either as the nested struct `{ "value": n }` or
+//! already flattened to `n`, both are accepted.
+use serde::{de, Deserialize, Deserializer};
+use std::{fmt, marker::PhantomData};
+
+struct ValueVisitor;
+
+impl<'de> de::Visitor<'de> for ValueVisitor {
+    type Value = u64;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("an unsigned integer or a struct with a `value` field")
+    }
+
+    fn visit_u64<E: de::Error>(self, v: u64) -> Result<u64, E> {
+        Ok(v)
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<u64, E> {
+        v.parse().map_err(E::custom)
+    }
+
+    fn visit_map<A: de::MapAccess<'de>>(self, mut map: A) -> Result<u64, A::Error> {
+        let mut value = None;
+        while let Some(key) = map.next_key::<String>()? {
+            if key == "value" {
+                value = Some(map.next_value_seed(Value)?);
+            } else {
+                map.next_value::<de::IgnoredAny>()?;
+            }
+        }
+        value.ok_or_else(|| de::Error::missing_field("value"))
+    }
+}
+
+/// `value` of a nested struct, itself a number or a decimal string
+struct Value;
+
+impl<'de> de::DeserializeSeed<'de> for Value {
+    type Value = u64;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<u64, D::Error> {
+        deserializer.deserialize_any(ValueVisitor)
+    }
+}
+
+fn deserialize_value<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u64, D::Error> {
+    if deserializer.is_human_readable() {
+        deserializer.deserialize_any(ValueVisitor)
+    } else {
+        // a struct with a single field has the layout of the field
+        u64::deserialize(deserializer)
+    }
+}
+
+macro_rules! value_type {
+    ($(#[$doc:meta])* $name:ident) => {
+        $(#[$doc])*
+        pub struct $name<T> {
+            value: u64,
+            phantom: PhantomData<fn() -> T>,
+        }
+
+        impl<T> $name<T> {
+            pub fn new(value: u64) -> Self {
+                $name {
+                    value,
+                    phantom: PhantomData,
+                }
+            }
+
+            pub fn value(&self) -> u64 {
+                self.value
+            }
+        }
+
+        impl<T> Clone for $name<T> {
+            fn clone(&self) -> Self {
+                *self
+            }
+        }
+
+        impl<T> Copy for $name<T> {}
+
+        impl<T> PartialEq for $name<T> {
+            fn eq(&self, other: &Self) -> bool {
+                self.value == other.value
+            }
+        }
+
+        impl<T> Eq for $name<T> {}
+
+        impl<T> fmt::Debug for $name<T> {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.debug_struct(stringify!($name))
+                    .field("value", &self.value)
+                    .finish()
+            }
+        }
+
+        impl<'de, T> Deserialize<'de> for $name<T> {
+            fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                deserialize_value(deserializer).map(Self::new)
+            }
+        }
+    };
+}
+
+value_type!(
+    /// `sui::balance::Balance<T>`
+    Balance
+);
+
+value_type!(
+    /// `sui::balance::Supply<T>`
+    Supply
+);
@@ -0,0 +1,33 @@
+//! `sui::dynamic_field::Field<Name, Value>`, the child object behind a dynamic field.
+//!
+//! `dynamic_field::add(&mut parent.id, name, value)` wraps `value` into a `Field`
+//! owned by the parent, so the value never shows up as an object of its own and
+//! has to be read through the children of the parent.
+use serde::Deserialize;
+use sui_sdk::types::{base_types::ObjectID, id::UID};
+
+#[derive(Deserialize, Debug)]
+pub struct Field<N, V> {
+    id: UID,
+    name: N,
+    value: V,
+}
+
+impl<N, V> Field<N, V> {
+    /// id of the field object, derived from the parent and the name
+    pub fn id(&self) -> ObjectID {
+        self.id.object_id().to_owned()
+    }
+
+    pub fn name(&self) -> &N {
+        &self.name
+    }
+
+    pub fn value(&self) -> &V {
+        &self.value
+    }
+
+    pub fn into_value(self) -> V {
+        self.value
+    }
+}
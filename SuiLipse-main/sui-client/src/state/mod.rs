@@ -1,64 +1,369 @@
+mod balance;
+mod dynamic_field;
+mod move_state;
+mod url;
+mod vec_set;
+
+pub use balance::{Balance, Supply};
+pub use dynamic_field::Field;
+pub use move_state::{MovePackage, MoveState, StateError};
+pub use url::Url;
+pub use vec_set::VecSet;
+
+use crate::{
+    oracle::Observation,
+    type_tag::{StructTag, TypeTag, TypeTagError},
+};
 use serde::Deserialize;
-use sui_sdk::types::{base_types::ObjectID, id::UID};
+use std::{collections::HashMap, marker::PhantomData, str::FromStr};
+use sui_sdk::types::{
+    base_types::{ObjectID, SuiAddress},
+    id::{ID, UID},
+};
 
 // ===== coin_pkg =====
 
+#[deprecated(note = "use `PoolCapability`, the capability of `create_pool`")]
 #[derive(Deserialize, Debug)]
 pub struct CapabilityState {
+    #[serde(rename = "id")]
     uid: UID,
 }
+/// `0x2::coin::Coin<T>`
 #[derive(Deserialize, Debug)]
 pub struct CoinState {
+    #[serde(rename = "id")]
     uid: UID,
-    balance: u64,
+    balance: Balance<AnyType>,
 }
 impl CoinState {
     pub fn uid_into(&self) -> ObjectID {
         self.uid.object_id().to_owned()
     }
+    pub fn balance(&self) -> u64 {
+        self.balance.value()
+    }
 }
+/// `0x2::coin::TreasuryCap<T>`
 #[derive(Deserialize, Debug)]
 pub struct TreasuryCapState {
+    #[serde(rename = "id")]
     uid: UID,
-    total_supply: u64,
+    total_supply: Supply<AnyType>,
+}
+impl TreasuryCapState {
+    pub fn uid_into(&self) -> ObjectID {
+        self.uid.object_id().to_owned()
+    }
+    pub fn total_supply(&self) -> u64 {
+        self.total_supply.value()
+    }
 }
 
 // ===== amm_pkg =====
 
+/// `sui_lipse::amm::Guardians`, the shared set of addresses allowed to mint a `PoolCapability`
 #[derive(Deserialize, Debug)]
-pub struct Pool {
+pub struct Guardians {
     id: UID,
-    name: String,
-    symbol: String,
-    reserve_x: u64,
-    reserve_y: u64,
-    lp_supply: u64,
+    guardians: VecSet<SuiAddress>,
+}
+impl Guardians {
+    pub fn id(&self) -> ObjectID {
+        self.id.object_id().to_owned()
+    }
+    pub fn guardians(&self) -> &[SuiAddress] {
+        self.guardians.contents()
+    }
+    pub fn is_guardian(&self, address: &SuiAddress) -> bool {
+        self.guardians.contains(address)
+    }
+}
+
+/// `sui_lipse::amm::PoolCapability`, minted by a guardian and passed to `create_pool`,
+/// whoever it is transferred to may create pools
+#[derive(Deserialize, Debug)]
+pub struct PoolCapability {
+    id: UID,
+}
+impl PoolCapability {
+    pub fn id(&self) -> ObjectID {
+        self.id.object_id().to_owned()
+    }
+}
+
+/// `sui_lipse::amm::PoolIdsList`, the shared registry of every created pool
+#[derive(Deserialize, Debug)]
+pub struct PoolIdsList {
+    id: UID,
+    // `VecSet<address>` on chain, an address has the layout of an object id
+    pool_ids: VecSet<ObjectID>,
+}
+impl PoolIdsList {
+    pub fn id(&self) -> ObjectID {
+        self.id.object_id().to_owned()
+    }
+    pub fn pool_ids(&self) -> &[ObjectID] {
+        self.pool_ids.contents()
+    }
+    pub fn contains(&self, pool: &ObjectID) -> bool {
+        self.pool_ids.contains(pool)
+    }
+}
+
+/// Rust stand-in for a Move type argument
+pub trait MoveType {
+    /// fully qualified Move type, e.g. `0x2::sui::SUI`; `None` matches any type
+    fn move_type() -> Option<String>;
+}
+
+/// Type argument left unchecked, the default of every phantom parameter
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AnyType;
+
+impl MoveType for AnyType {
+    fn move_type() -> Option<String> {
+        None
+    }
+}
+
+impl MoveType for ID {
+    fn move_type() -> Option<String> {
+        Some("0x2::object::ID".to_string())
+    }
+}
+
+/// `sui_lipse::amm::LP_TOKEN<V, X, Y>`
+pub struct LpToken<V, X, Y>(PhantomData<(V, X, Y)>);
+
+/// Type arguments `<V, X, Y>` of a pool, read from the type tag of a `Pool` or of its events
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct PoolType {
+    pub verifier: TypeTag,
+    pub token_x: TypeTag,
+    pub token_y: TypeTag,
+}
+
+impl PoolType {
+    /// `[V, X, Y]`, `None` for any other number of type arguments
+    pub fn from_params(type_params: &[TypeTag]) -> Option<Self> {
+        match type_params {
+            [verifier, token_x, token_y] => Some(PoolType {
+                verifier: verifier.clone(),
+                token_x: token_x.clone(),
+                token_y: token_y.clone(),
+            }),
+            _ => None,
+        }
+    }
+
+    /// `<package>::amm::Pool<V, X, Y>` of any package
+    pub fn from_tag(tag: &StructTag) -> Result<Self, TypeTagError> {
+        Ok(Self::from_params(tag.pool_types()?).expect("three pool type parameters"))
+    }
+
+    /// `<package>::amm::Pool<V, X, Y>`, `None` for any other type
+    pub fn parse(type_tag: &str) -> Option<Self> {
+        Self::from_tag(&StructTag::from_str(type_tag).ok()?).ok()
+    }
+
+    /// `<package>::amm::Pool<V, X, Y>`
+    pub fn pool(&self, package: ObjectID) -> StructTag {
+        StructTag::pool(
+            package,
+            self.verifier.clone(),
+            self.token_x.clone(),
+            self.token_y.clone(),
+        )
+    }
+
+    /// `<package>::amm::LP_TOKEN<V, X, Y>`
+    pub fn lp_token(&self, package: ObjectID) -> StructTag {
+        StructTag::lp_token(
+            package,
+            self.verifier.clone(),
+            self.token_x.clone(),
+            self.token_y.clone(),
+        )
+    }
+
+    /// `[V, X, Y]`, the type arguments of every pool script
+    pub fn type_params(&self) -> [&TypeTag; 3] {
+        [&self.verifier, &self.token_x, &self.token_y]
+    }
+
+    /// whether the markers `V`, `X` and `Y` accept these type arguments
+    pub fn matches<V: MoveType, X: MoveType, Y: MoveType>(&self) -> bool {
+        move_state::accepts::<V>(&self.verifier)
+            && move_state::accepts::<X>(&self.token_x)
+            && move_state::accepts::<Y>(&self.token_y)
+    }
+}
+
+/// `sui_lipse::amm::Pool<V, X, Y>`, fields in the order of the Move struct
+#[derive(Deserialize, Debug)]
+#[serde(bound = "")]
+pub struct Pool<V = AnyType, X = AnyType, Y = AnyType> {
+    id: UID,
+    reserve_x: Balance<X>,
+    reserve_y: Balance<Y>,
+    lp_supply: Supply<LpToken<V, X, Y>>,
     fee_percentage: u64, //[1,10000] --> [0.01%, 100%]
+    last_block_timestamp: u64,
+    #[serde(with = "crate::serde_str::u128")]
+    last_price_x_cumulative: u128,
+    #[serde(with = "crate::serde_str::u128")]
+    last_price_y_cumulative: u128,
+    locked: bool,
+    emergency: bool,
+}
+impl<V, X, Y> Pool<V, X, Y> {
+    pub fn id(&self) -> ObjectID {
+        self.id.object_id().to_owned()
+    }
+    pub fn reserve_x(&self) -> u64 {
+        self.reserve_x.value()
+    }
+    pub fn reserve_y(&self) -> u64 {
+        self.reserve_y.value()
+    }
+    pub fn lp_supply(&self) -> u64 {
+        self.lp_supply.value()
+    }
+    /// `(reserve_x, reserve_y, lp_supply)`, same as `get_reserves`
+    pub fn reserves(&self) -> (u64, u64, u64) {
+        (self.reserve_x(), self.reserve_y(), self.lp_supply())
+    }
+    pub fn fee_percentage(&self) -> u64 {
+        self.fee_percentage
+    }
+    pub fn last_block_timestamp(&self) -> u64 {
+        self.last_block_timestamp
+    }
+    pub fn last_price_x_cumulative(&self) -> u128 {
+        self.last_price_x_cumulative
+    }
+    pub fn last_price_y_cumulative(&self) -> u128 {
+        self.last_price_y_cumulative
+    }
+    /// set during flash loans
+    pub fn is_locked(&self) -> bool {
+        self.locked
+    }
+    pub fn is_emergency(&self) -> bool {
+        self.emergency
+    }
+    /// cumulative prices of this snapshot, see [`crate::oracle::twap`]
+    pub fn observation(&self) -> Observation {
+        Observation {
+            timestamp: self.last_block_timestamp,
+            price_x_cumulative: self.last_price_x_cumulative,
+            price_y_cumulative: self.last_price_y_cumulative,
+        }
+    }
 }
 
 // ===== NFT =====
+
+/// `sui_lipse::nft_collection::Card`
 #[derive(Deserialize, Debug)]
-pub struct NFTState {
-    uid: UID,
+pub struct Card {
+    id: UID,
     name: String,
     description: String,
-    url: String, //i
-}
-
-// #[async_trait]
-// pub trait Client {
-//     fn getObject<T>(&self) -> Result<T, anyhow::Error> {}
-// }
-
-// #[async_trait]
-// impl Client for AmmClient {
-//     async fn get_obj_state<T>(&self, object_id: ObjectID) -> SuiObject<SuiRawData> {
-//         let obj = self
-//             .client
-//             .read_api()
-//             .get_object(object_id)
-//             .await?
-//             .into_object()?;
-//         obj
-//     }
-// }
+    url: Url,
+}
+impl Card {
+    pub fn id(&self) -> ObjectID {
+        self.id.object_id().to_owned()
+    }
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+    pub fn description(&self) -> &str {
+        &self.description
+    }
+    pub fn url(&self) -> &Url {
+        &self.url
+    }
+}
+
+#[deprecated(note = "use `Card`, the Move struct has an `id` field")]
+pub type NFTState = Card;
+
+/// `sui_lipse::nft_collection::CopyCard`
+#[derive(Deserialize, Debug)]
+pub struct CopyCard {
+    id: UID,
+    name: String,
+    url: Url,
+}
+impl CopyCard {
+    pub fn id(&self) -> ObjectID {
+        self.id.object_id().to_owned()
+    }
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+    pub fn url(&self) -> &Url {
+        &self.url
+    }
+}
+
+/// `sui_lipse::nft_collection::CardCollection<Card>`
+///
+/// The cards are dynamic fields of the collection keyed by their id, only the ids
+/// are part of the object itself.
+#[derive(Deserialize, Debug)]
+pub struct CardCollection {
+    id: UID,
+    cards: VecSet<ID>,
+    max_capacity: u64,
+}
+impl CardCollection {
+    pub fn id(&self) -> ObjectID {
+        self.id.object_id().to_owned()
+    }
+    /// ids of the cards, in insertion order
+    pub fn card_ids(&self) -> Vec<ObjectID> {
+        self.cards.iter().map(|id| id.bytes).collect()
+    }
+    pub fn len(&self) -> usize {
+        self.cards.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.cards.is_empty()
+    }
+    pub fn max_capacity(&self) -> u64 {
+        self.max_capacity
+    }
+    /// Cards of the collection in insertion order, from its dynamic field children
+    ///
+    /// Children that are not cards of this collection are ignored, cards without a
+    /// field among `fields` are left out.
+    pub fn resolve_cards(&self, fields: Vec<Field<ID, Card>>) -> Vec<Card> {
+        let mut cards: HashMap<ObjectID, Card> = fields
+            .into_iter()
+            .map(|field| (field.name().bytes, field.into_value()))
+            .collect();
+        self.cards
+            .iter()
+            .filter_map(|id| cards.remove(&id.bytes))
+            .collect()
+    }
+}
+
+/// `sui_lipse::sbt::SBT`, a soulbound token whose url is a base64 svg
+#[derive(Deserialize, Debug)]
+pub struct SBT {
+    id: UID,
+    url: Url,
+}
+impl SBT {
+    pub fn id(&self) -> ObjectID {
+        self.id.object_id().to_owned()
+    }
+    pub fn url(&self) -> &Url {
+        &self.url
+    }
+}
@@ -0,0 +1,196 @@
+//! Binding between the state types and the Move structs they mirror.
+//!
+//! A fetched object is only deserialized once its type tag names the expected
+//! struct, so reading a `Coin` as a `Pool` fails with [`StateError::TypeMismatch`]
+//! instead of a confusing missing field.
+//!
+//! Every state type lists its fields in the order of the Move struct, so the same
+//! type decodes both the JSON fields of `SuiParsedData` and the BCS bytes of
+//! `SuiRawData`.
+use super::{
+    Card, CardCollection, CoinState, CopyCard, Field, Guardians, MoveType, Pool, PoolCapability,
+    PoolIdsList, PoolType, TreasuryCapState, SBT,
+};
+use crate::type_tag::{StructTag, TypeTag, TypeTagError, SUI_FRAMEWORK_ADDRESS};
+use serde::de::DeserializeOwned;
+use std::str::FromStr;
+use sui_sdk::types::base_types::ObjectID;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum StateError {
+    /// a package, or an object without Move contents
+    #[error("object {0} is not a Move object")]
+    NotMoveObject(ObjectID),
+    #[error("object {id} has type {found}, expected {expected}")]
+    TypeMismatch {
+        id: ObjectID,
+        expected: String,
+        found: String,
+    },
+    /// the type tag matched but the contents did not
+    #[error("object {0} could not be deserialized: {1}")]
+    Deserialize(ObjectID, String),
+    /// BCS bytes not matching the Move layout of the type
+    #[error("object {0} could not be decoded from BCS: {1}")]
+    Bcs(ObjectID, String),
+    #[error(transparent)]
+    TypeTag(#[from] TypeTagError),
+}
+
+/// Package declaring a Move struct
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MovePackage {
+    /// `0x2`
+    Framework,
+    /// the `sui_lipse` package, its address is only known at runtime
+    SuiLipse,
+}
+
+/// A state type mirroring the Move struct `<PACKAGE>::<MODULE>::<NAME>`
+pub trait MoveState: DeserializeOwned {
+    const PACKAGE: MovePackage;
+    const MODULE: &'static str;
+    const NAME: &'static str;
+    /// number of type parameters of the struct
+    const TYPE_PARAMS: usize = 0;
+
+    /// whether the type arguments of a fetched object fit this Rust type, any by default
+    fn accepts(_type_params: &[TypeTag], _package: ObjectID) -> bool {
+        true
+    }
+
+    /// `address::module::name`, the sui_lipse types are looked up in `package`
+    fn expected_type(package: ObjectID) -> String {
+        let address = match Self::PACKAGE {
+            MovePackage::Framework => SUI_FRAMEWORK_ADDRESS.to_string(),
+            MovePackage::SuiLipse => package.to_string(),
+        };
+        format!("{}::{}::{}", address, Self::MODULE, Self::NAME)
+    }
+
+    /// whether `tag` names this type, the sui_lipse types are looked up in `package`
+    fn matches_tag(tag: &StructTag, package: ObjectID) -> bool {
+        let address = match Self::PACKAGE {
+            MovePackage::Framework => {
+                ObjectID::from_hex_literal(SUI_FRAMEWORK_ADDRESS).expect("framework address")
+            }
+            MovePackage::SuiLipse => package,
+        };
+        tag.address == address
+            && tag.module == Self::MODULE
+            && tag.name == Self::NAME
+            && tag.type_params.len() == Self::TYPE_PARAMS
+            && Self::accepts(&tag.type_params, package)
+    }
+
+    /// Check the type tag `type_` of object `id` before deserializing it
+    fn check_type(id: ObjectID, type_: &str, package: ObjectID) -> Result<StructTag, StateError> {
+        let tag = StructTag::from_str(type_)?;
+        if Self::matches_tag(&tag, package) {
+            Ok(tag)
+        } else {
+            Err(StateError::TypeMismatch {
+                id,
+                expected: Self::expected_type(package),
+                found: type_.to_string(),
+            })
+        }
+    }
+
+    /// Check the type of a fetched object, then deserialize its JSON `fields`
+    fn from_fields(
+        id: ObjectID,
+        type_: &str,
+        fields: serde_json::Value,
+        package: ObjectID,
+    ) -> Result<Self, StateError> {
+        Ok(Self::from_fields_with_type(id, type_, fields, package)?.0)
+    }
+
+    /// Same as [`MoveState::from_fields`], keeping the parsed type tag
+    fn from_fields_with_type(
+        id: ObjectID,
+        type_: &str,
+        fields: serde_json::Value,
+        package: ObjectID,
+    ) -> Result<(Self, StructTag), StateError> {
+        let tag = Self::check_type(id, type_, package)?;
+        let state = serde_json::from_value(fields)
+            .map_err(|e| StateError::Deserialize(id, e.to_string()))?;
+        Ok((state, tag))
+    }
+
+    /// Check the type of a fetched object, then decode its BCS contents
+    fn from_bcs(
+        id: ObjectID,
+        type_: &str,
+        bcs_bytes: &[u8],
+        package: ObjectID,
+    ) -> Result<Self, StateError> {
+        Self::check_type(id, type_, package)?;
+        bcs::from_bytes(bcs_bytes).map_err(|e| StateError::Bcs(id, e.to_string()))
+    }
+}
+
+/// type argument `param` against a marker, markers without a type accept anything
+pub(super) fn accepts<T: MoveType>(param: &TypeTag) -> bool {
+    match T::move_type() {
+        Some(t) => TypeTag::from_str(&t).ok().as_ref() == Some(param),
+        None => true,
+    }
+}
+
+macro_rules! move_state {
+    ($ty:ty, $package:ident, $module:literal, $name:literal) => {
+        move_state!($ty, $package, $module, $name, 0);
+    };
+    ($ty:ty, $package:ident, $module:literal, $name:literal, $params:literal) => {
+        impl MoveState for $ty {
+            const PACKAGE: MovePackage = MovePackage::$package;
+            const MODULE: &'static str = $module;
+            const NAME: &'static str = $name;
+            const TYPE_PARAMS: usize = $params;
+        }
+    };
+}
+
+move_state!(CoinState, Framework, "coin", "Coin", 1);
+move_state!(TreasuryCapState, Framework, "coin", "TreasuryCap", 1);
+move_state!(Guardians, SuiLipse, "amm", "Guardians");
+move_state!(PoolCapability, SuiLipse, "amm", "PoolCapability");
+move_state!(PoolIdsList, SuiLipse, "amm", "PoolIdsList");
+move_state!(Card, SuiLipse, "nft_collection", "Card");
+move_state!(CopyCard, SuiLipse, "nft_collection", "CopyCard");
+move_state!(
+    CardCollection,
+    SuiLipse,
+    "nft_collection",
+    "CardCollection",
+    1
+);
+move_state!(SBT, SuiLipse, "sbt", "SBT");
+
+impl<V: MoveType, X: MoveType, Y: MoveType> MoveState for Pool<V, X, Y> {
+    const PACKAGE: MovePackage = MovePackage::SuiLipse;
+    const MODULE: &'static str = "amm";
+    const NAME: &'static str = "Pool";
+    const TYPE_PARAMS: usize = 3;
+
+    fn accepts(type_params: &[TypeTag], _package: ObjectID) -> bool {
+        matches!(PoolType::from_params(type_params), Some(ty) if ty.matches::<V, X, Y>())
+    }
+}
+
+impl<N: MoveType + DeserializeOwned, V: MoveState> MoveState for Field<N, V> {
+    const PACKAGE: MovePackage = MovePackage::Framework;
+    const MODULE: &'static str = "dynamic_field";
+    const NAME: &'static str = "Field";
+    const TYPE_PARAMS: usize = 2;
+
+    /// `N` against its marker, `V` against the struct it mirrors
+    fn accepts(type_params: &[TypeTag], package: ObjectID) -> bool {
+        accepts::<N>(&type_params[0])
+            && matches!(type_params[1].as_struct(), Ok(tag) if V::matches_tag(tag, package))
+    }
+}
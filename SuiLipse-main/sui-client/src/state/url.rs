@@ -0,0 +1,67 @@
+//! `sui::url::Url`, an ascii `url: String` on chain.
+//!
+//! The JSON RPC renders it either as the nested struct `{ "url": s }` or as the bare string.
+use serde::{de, Deserialize, Deserializer};
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Url {
+    url: String,
+}
+
+impl Url {
+    pub fn new(url: impl Into<String>) -> Self {
+        Url { url: url.into() }
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.url
+    }
+}
+
+impl fmt::Display for Url {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.url)
+    }
+}
+
+struct UrlVisitor;
+
+impl<'de> de::Visitor<'de> for UrlVisitor {
+    type Value = String;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a string or a struct with a `url` field")
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<String, E> {
+        Ok(v.to_string())
+    }
+
+    fn visit_string<E: de::Error>(self, v: String) -> Result<String, E> {
+        Ok(v)
+    }
+
+    fn visit_map<A: de::MapAccess<'de>>(self, mut map: A) -> Result<String, A::Error> {
+        let mut url = None;
+        while let Some(key) = map.next_key::<String>()? {
+            if key == "url" {
+                url = Some(map.next_value()?);
+            } else {
+                map.next_value::<de::IgnoredAny>()?;
+            }
+        }
+        url.ok_or_else(|| de::Error::missing_field("url"))
+    }
+}
+
+impl<'de> Deserialize<'de> for Url {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        if deserializer.is_human_readable() {
+            deserializer.deserialize_any(UrlVisitor).map(Url::new)
+        } else {
+            // a struct with a single field has the layout of the field
+            String::deserialize(deserializer).map(Url::new)
+        }
+    }
+}
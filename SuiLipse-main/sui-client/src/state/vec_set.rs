@@ -0,0 +1,95 @@
+//! `sui::vec_set::VecSet<K>`, a `contents: vector<K>` on chain.
+//!
+//! Accepted as the nested struct `{ "contents": [..] }` or as the bare array.
+use serde::{de, Deserialize, Deserializer};
+use std::{fmt, marker::PhantomData};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VecSet<K> {
+    contents: Vec<K>,
+}
+
+impl<K: PartialEq> VecSet<K> {
+    pub fn contains(&self, key: &K) -> bool {
+        self.contents.contains(key)
+    }
+}
+
+impl<K> VecSet<K> {
+    pub fn new(contents: Vec<K>) -> Self {
+        VecSet { contents }
+    }
+
+    /// keys in insertion order
+    pub fn contents(&self) -> &[K] {
+        &self.contents
+    }
+
+    pub fn len(&self) -> usize {
+        self.contents.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.contents.is_empty()
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, K> {
+        self.contents.iter()
+    }
+
+    pub fn into_vec(self) -> Vec<K> {
+        self.contents
+    }
+}
+
+struct ContentsVisitor<K>(PhantomData<K>);
+
+impl<'de, K: Deserialize<'de>> de::Visitor<'de> for ContentsVisitor<K> {
+    type Value = Vec<K>;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a sequence or a struct with a `contents` field")
+    }
+
+    fn visit_seq<A: de::SeqAccess<'de>>(self, mut seq: A) -> Result<Vec<K>, A::Error> {
+        let mut contents = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+        while let Some(key) = seq.next_element()? {
+            contents.push(key);
+        }
+        Ok(contents)
+    }
+
+    fn visit_map<A: de::MapAccess<'de>>(self, mut map: A) -> Result<Vec<K>, A::Error> {
+        let mut contents = None;
+        while let Some(key) = map.next_key::<String>()? {
+            if key == "contents" {
+                contents = Some(map.next_value()?);
+            } else {
+                map.next_value::<de::IgnoredAny>()?;
+            }
+        }
+        contents.ok_or_else(|| de::Error::missing_field("contents"))
+    }
+}
+
+impl<'de, K: Deserialize<'de>> Deserialize<'de> for VecSet<K> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        if deserializer.is_human_readable() {
+            deserializer
+                .deserialize_any(ContentsVisitor(PhantomData))
+                .map(VecSet::new)
+        } else {
+            // a struct with a single field has the layout of the field
+            Vec::deserialize(deserializer).map(VecSet::new)
+        }
+    }
+}
+
+impl<'a, K> IntoIterator for &'a VecSet<K> {
+    type Item = &'a K;
+    type IntoIter = std::slice::Iter<'a, K>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.contents.iter()
+    }
+}
@@ -0,0 +1,372 @@
+//! Move type tags, parsed from and rendered to strings such as
+//! `0x2::coin::Coin<0x2::sui::SUI>` or `<pkg>::amm::Pool<V, X, Y>`.
+//!
+//! Move calls take their type arguments as tags; building them from the package and
+//! coin types keeps them deterministic instead of reading them back from objects.
+use std::{fmt, str::FromStr};
+use sui_sdk::types::base_types::ObjectID;
+use thiserror::Error;
+
+/// `0x2`, the Sui framework
+pub const SUI_FRAMEWORK_ADDRESS: &str = "0x2";
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum TypeTagError {
+    #[error("empty type tag")]
+    Empty,
+    #[error("invalid address {0}")]
+    InvalidAddress(String),
+    #[error("invalid identifier {0:?}")]
+    InvalidIdentifier(String),
+    /// a token other than the one the grammar expects at this position
+    #[error("expected {expected} at {position} in {input}")]
+    Unexpected {
+        expected: &'static str,
+        position: usize,
+        input: String,
+    },
+    /// a valid tag of another type than the one asked for
+    #[error("expected {expected}, found {found}")]
+    WrongType { expected: String, found: String },
+}
+
+/// Any Move type
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum TypeTag {
+    Bool,
+    U8,
+    U64,
+    U128,
+    Address,
+    Signer,
+    Vector(Box<TypeTag>),
+    Struct(StructTag),
+}
+
+/// `address::module::name<type_params>`
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct StructTag {
+    pub address: ObjectID,
+    pub module: String,
+    pub name: String,
+    pub type_params: Vec<TypeTag>,
+}
+
+fn is_identifier(s: &str) -> bool {
+    let mut chars = s.chars();
+    matches!(chars.next(), Some(c) if c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+fn identifier(s: &str) -> Result<String, TypeTagError> {
+    if is_identifier(s) {
+        Ok(s.to_string())
+    } else {
+        Err(TypeTagError::InvalidIdentifier(s.to_string()))
+    }
+}
+
+fn parse_address(s: &str) -> Result<ObjectID, TypeTagError> {
+    let invalid = || TypeTagError::InvalidAddress(s.to_string());
+    let hex = s.strip_prefix("0x").ok_or_else(invalid)?;
+    if hex.is_empty() || hex.len() > 40 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(invalid());
+    }
+    ObjectID::from_hex_literal(s).map_err(|_| invalid())
+}
+
+impl StructTag {
+    pub fn new(
+        address: &str,
+        module: &str,
+        name: &str,
+        type_params: Vec<TypeTag>,
+    ) -> Result<Self, TypeTagError> {
+        Ok(StructTag {
+            address: parse_address(address)?,
+            module: identifier(module)?,
+            name: identifier(name)?,
+            type_params,
+        })
+    }
+
+    /// `0x2::coin::Coin<T>`
+    pub fn coin(coin_type: TypeTag) -> Self {
+        Self::framework("coin", "Coin", vec![coin_type])
+    }
+
+    /// `0x2::coin::TreasuryCap<T>`
+    pub fn treasury_cap(coin_type: TypeTag) -> Self {
+        Self::framework("coin", "TreasuryCap", vec![coin_type])
+    }
+
+    /// `<package>::amm::Pool<V, X, Y>`
+    pub fn pool(package: ObjectID, v: TypeTag, x: TypeTag, y: TypeTag) -> Self {
+        Self::amm(package, "Pool", v, x, y)
+    }
+
+    /// `<package>::amm::LP_TOKEN<V, X, Y>`
+    pub fn lp_token(package: ObjectID, v: TypeTag, x: TypeTag, y: TypeTag) -> Self {
+        Self::amm(package, "LP_TOKEN", v, x, y)
+    }
+
+    fn framework(module: &str, name: &str, type_params: Vec<TypeTag>) -> Self {
+        Self::new(SUI_FRAMEWORK_ADDRESS, module, name, type_params).expect("framework struct tag")
+    }
+
+    fn amm(package: ObjectID, name: &str, v: TypeTag, x: TypeTag, y: TypeTag) -> Self {
+        StructTag {
+            address: package,
+            module: "amm".to_string(),
+            name: name.to_string(),
+            type_params: vec![v, x, y],
+        }
+    }
+
+    /// `module::name` without the address and the type parameters
+    pub fn qualified_name(&self) -> String {
+        format!("{}::{}", self.module, self.name)
+    }
+
+    fn expect(
+        &self,
+        address: Option<&ObjectID>,
+        module: &str,
+        name: &str,
+        arity: usize,
+        expected: &str,
+    ) -> Result<&[TypeTag], TypeTagError> {
+        let matches = (address.is_none() || address == Some(&self.address))
+            && self.module == module
+            && self.name == name
+            && self.type_params.len() == arity;
+        if matches {
+            Ok(&self.type_params)
+        } else {
+            Err(TypeTagError::WrongType {
+                expected: expected.to_string(),
+                found: self.to_string(),
+            })
+        }
+    }
+
+    /// `T` of a `0x2::coin::Coin<T>`
+    pub fn coin_type(&self) -> Result<&TypeTag, TypeTagError> {
+        let framework = parse_address(SUI_FRAMEWORK_ADDRESS)?;
+        Ok(&self.expect(Some(&framework), "coin", "Coin", 1, "0x2::coin::Coin<T>")?[0])
+    }
+
+    /// `T` of a `0x2::coin::TreasuryCap<T>`
+    pub fn treasury_cap_type(&self) -> Result<&TypeTag, TypeTagError> {
+        let framework = parse_address(SUI_FRAMEWORK_ADDRESS)?;
+        let params = self.expect(
+            Some(&framework),
+            "coin",
+            "TreasuryCap",
+            1,
+            "0x2::coin::TreasuryCap<T>",
+        )?;
+        Ok(&params[0])
+    }
+
+    /// `[V, X, Y]` of an `amm::Pool<V, X, Y>` of any package
+    pub fn pool_types(&self) -> Result<&[TypeTag], TypeTagError> {
+        self.expect(None, "amm", "Pool", 3, "amm::Pool<V, X, Y>")
+    }
+
+    /// `[V, X, Y]` of an `amm::LP_TOKEN<V, X, Y>` of any package
+    pub fn lp_token_types(&self) -> Result<&[TypeTag], TypeTagError> {
+        self.expect(None, "amm", "LP_TOKEN", 3, "amm::LP_TOKEN<V, X, Y>")
+    }
+}
+
+impl TypeTag {
+    /// the struct tag, `WrongType` for a primitive or a vector
+    pub fn as_struct(&self) -> Result<&StructTag, TypeTagError> {
+        match self {
+            TypeTag::Struct(tag) => Ok(tag),
+            _ => Err(TypeTagError::WrongType {
+                expected: "a struct".to_string(),
+                found: self.to_string(),
+            }),
+        }
+    }
+}
+
+impl From<StructTag> for TypeTag {
+    fn from(tag: StructTag) -> Self {
+        TypeTag::Struct(tag)
+    }
+}
+
+impl fmt::Display for StructTag {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}::{}::{}", self.address, self.module, self.name)?;
+        if let Some((first, rest)) = self.type_params.split_first() {
+            write!(f, "<{}", first)?;
+            for param in rest {
+                write!(f, ", {}", param)?;
+            }
+            f.write_str(">")?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for TypeTag {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TypeTag::Bool => f.write_str("bool"),
+            TypeTag::U8 => f.write_str("u8"),
+            TypeTag::U64 => f.write_str("u64"),
+            TypeTag::U128 => f.write_str("u128"),
+            TypeTag::Address => f.write_str("address"),
+            TypeTag::Signer => f.write_str("signer"),
+            TypeTag::Vector(inner) => write!(f, "vector<{}>", inner),
+            TypeTag::Struct(tag) => tag.fmt(f),
+        }
+    }
+}
+
+/// Recursive descent over the tag, whitespace is allowed around `<`, `>` and `,`
+struct Parser<'a> {
+    input: &'a str,
+    position: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn rest(&self) -> &'a str {
+        &self.input[self.position..]
+    }
+
+    fn skip_whitespace(&mut self) {
+        let rest = self.rest();
+        self.position += rest.len() - rest.trim_start().len();
+    }
+
+    fn unexpected(&self, expected: &'static str) -> TypeTagError {
+        TypeTagError::Unexpected {
+            expected,
+            position: self.position,
+            input: self.input.to_string(),
+        }
+    }
+
+    fn eat(&mut self, token: &str) -> bool {
+        self.skip_whitespace();
+        if self.rest().starts_with(token) {
+            self.position += token.len();
+            true
+        } else {
+            false
+        }
+    }
+
+    /// `[A-Za-z0-9_]*`, an address or an identifier
+    fn word(&mut self) -> &'a str {
+        self.skip_whitespace();
+        let rest = self.rest();
+        let len = rest
+            .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+            .unwrap_or(rest.len());
+        self.position += len;
+        &rest[..len]
+    }
+
+    fn type_tag(&mut self) -> Result<TypeTag, TypeTagError> {
+        let start = self.position;
+        let word = self.word();
+        Ok(match word {
+            "" => return Err(self.unexpected("a type")),
+            "bool" => TypeTag::Bool,
+            "u8" => TypeTag::U8,
+            "u64" => TypeTag::U64,
+            "u128" => TypeTag::U128,
+            "address" => TypeTag::Address,
+            "signer" => TypeTag::Signer,
+            "vector" => {
+                if !self.eat("<") {
+                    return Err(self.unexpected("<"));
+                }
+                let inner = self.type_tag()?;
+                if !self.eat(">") {
+                    return Err(self.unexpected(">"));
+                }
+                TypeTag::Vector(Box::new(inner))
+            }
+            _ => {
+                self.position = start;
+                TypeTag::Struct(self.struct_tag()?)
+            }
+        })
+    }
+
+    fn struct_tag(&mut self) -> Result<StructTag, TypeTagError> {
+        let address = parse_address(self.word())?;
+        if !self.eat("::") {
+            return Err(self.unexpected("::"));
+        }
+        let module = identifier(self.word())?;
+        if !self.eat("::") {
+            return Err(self.unexpected("::"));
+        }
+        let name = identifier(self.word())?;
+
+        let mut type_params = Vec::new();
+        if self.eat("<") {
+            loop {
+                type_params.push(self.type_tag()?);
+                if self.eat(">") {
+                    break;
+                }
+                if !self.eat(",") {
+                    return Err(self.unexpected(", or >"));
+                }
+            }
+        }
+
+        Ok(StructTag {
+            address,
+            module,
+            name,
+            type_params,
+        })
+    }
+
+    fn finish<T>(mut self, parsed: T) -> Result<T, TypeTagError> {
+        self.skip_whitespace();
+        if self.rest().is_empty() {
+            Ok(parsed)
+        } else {
+            Err(self.unexpected("end of input"))
+        }
+    }
+}
+
+fn parse<'a, T>(
+    input: &'a str,
+    f: impl FnOnce(&mut Parser<'a>) -> Result<T, TypeTagError>,
+) -> Result<T, TypeTagError> {
+    if input.trim().is_empty() {
+        return Err(TypeTagError::Empty);
+    }
+    let mut parser = Parser { input, position: 0 };
+    let parsed = f(&mut parser)?;
+    parser.finish(parsed)
+}
+
+impl FromStr for TypeTag {
+    type Err = TypeTagError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse(s, Parser::type_tag)
+    }
+}
+
+impl FromStr for StructTag {
+    type Err = TypeTagError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse(s, Parser::struct_tag)
+    }
+}
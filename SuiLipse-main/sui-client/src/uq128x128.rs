@@ -0,0 +1,115 @@
+//! Implementation of FixedPoint u128, bit-exact with `sui_lipse::uq128x128`.
+//!
+//! Like [`crate::uq64x64`], the scale is `Q128 = 2^128 - 1` as in the Move module.
+use crate::amm_math::MathError;
+use ethnum::U256;
+use serde::{Deserialize, Serialize};
+use std::{convert::TryFrom, fmt};
+
+pub const Q128: U256 = U256::from_words(0, u128::MAX);
+
+/// When a and b are equals.
+pub const EQUAL: u8 = 0;
+
+/// When a is less than b equals.
+pub const LESS_THAN: u8 = 1;
+
+/// When a is greater than b.
+pub const GREATER_THAN: u8 = 2;
+
+#[derive(
+    Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash,
+)]
+pub struct UQ128x128 {
+    #[serde(with = "crate::serde_str::u256")]
+    v: U256,
+}
+
+impl UQ128x128 {
+    /// Wrap the raw `u256` stored on chain
+    pub fn from_raw(v: U256) -> Self {
+        UQ128x128 { v }
+    }
+
+    /// Encode `u128` to `UQ128x128`
+    pub fn encode(x: u128) -> Self {
+        UQ128x128 {
+            v: U256::from(x) * Q128,
+        }
+    }
+
+    /// Decode a `UQ128x128` into a `u128` by truncating after the radix point.
+    pub fn decode(&self) -> Result<u128, MathError> {
+        u128::try_from(self.v / Q128).map_err(|_| MathError::Overflow)
+    }
+
+    pub fn to_u256(&self) -> U256 {
+        self.v
+    }
+
+    /// Multiply a `UQ128x128` by a `u128`, returning a `UQ128x128`
+    pub fn mul(&self, y: u128) -> Result<Self, MathError> {
+        let v = self
+            .v
+            .checked_mul(U256::from(y))
+            .ok_or(MathError::Overflow)?;
+        Ok(UQ128x128 { v })
+    }
+
+    /// Divide a `UQ128x128` by a `u128`, returning a `UQ128x128`.
+    pub fn div(&self, y: u128) -> Result<Self, MathError> {
+        if y == 0 {
+            return Err(MathError::DivideByZero);
+        }
+        Ok(UQ128x128 {
+            v: self.v / U256::from(y),
+        })
+    }
+
+    /// Returns a `UQ128x128` which represents the ratio of the numerator to the denominator.
+    pub fn fraction(numerator: u128, denominator: u128) -> Result<Self, MathError> {
+        if denominator == 0 {
+            return Err(MathError::DivideByZero);
+        }
+        let r = U256::from(numerator) * Q128;
+        Ok(UQ128x128 {
+            v: r / U256::from(denominator),
+        })
+    }
+
+    /// Compare two `UQ128x128` numbers, `EQUAL`, `LESS_THAN` or `GREATER_THAN` as on chain
+    ///
+    /// The derived `Ord` orders by the raw value too, this keeps the Move return codes.
+    pub fn compare(&self, other: &Self) -> u8 {
+        if self.v == other.v {
+            EQUAL
+        } else if self.v < other.v {
+            LESS_THAN
+        } else {
+            GREATER_THAN
+        }
+    }
+
+    /// Check if `UQ128x128` is zero
+    pub fn is_zero(&self) -> bool {
+        self.v == 0
+    }
+
+    /// Lossy conversion for display and analytics
+    pub fn to_f64(&self) -> f64 {
+        self.v.as_f64() / Q128.as_f64()
+    }
+}
+
+impl fmt::Display for UQ128x128 {
+    /// Decimal representation, `{:.N}` picks the number of fractional digits (default and max 18)
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let digits = f.precision().unwrap_or(18).min(18);
+        let int = self.v / Q128;
+        if digits == 0 {
+            return write!(f, "{}", int);
+        }
+        let frac = (self.v % Q128) * U256::from(10u128.pow(digits as u32)) / Q128;
+        write!(f, "{}.{:0width$}", int, frac, width = digits)
+    }
+}
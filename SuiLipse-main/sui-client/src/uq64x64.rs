@@ -0,0 +1,114 @@
+//! Implementation of FixedPoint u64, bit-exact with `sui_lipse::uq64x64`.
+//!
+//! Note that the Move module scales by `Q64 = 2^64 - 1` rather than `2^64`,
+//! this is kept as is so that cumulative prices match the on-chain values.
+use crate::amm_math::MathError;
+use serde::{Deserialize, Serialize};
+use std::{convert::TryFrom, fmt};
+
+pub const Q64: u128 = 18446744073709551615;
+
+/// When a and b are equals.
+pub const EQUAL: u8 = 0;
+
+/// When a is less than b equals.
+pub const LESS_THAN: u8 = 1;
+
+/// When a is greater than b.
+pub const GREATER_THAN: u8 = 2;
+
+/// The resource to store `UQ64x64`.
+#[derive(
+    Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash,
+)]
+pub struct UQ64x64 {
+    #[serde(with = "crate::serde_str::u128")]
+    v: u128,
+}
+
+impl UQ64x64 {
+    /// Wrap the raw `u128` stored on chain
+    pub fn from_raw(v: u128) -> Self {
+        UQ64x64 { v }
+    }
+
+    /// Encode `u64` to `UQ64x64`
+    pub fn encode(x: u64) -> Self {
+        // (2^64 - 1)^2 < 2^128
+        UQ64x64 { v: x as u128 * Q64 }
+    }
+
+    /// Decode a `UQ64x64` into a `u64` by truncating after the radix point.
+    pub fn decode(&self) -> Result<u64, MathError> {
+        u64::try_from(self.v / Q64).map_err(|_| MathError::Overflow)
+    }
+
+    /// Get `u128` from UQ64x64
+    pub fn to_u128(&self) -> u128 {
+        self.v
+    }
+
+    /// Multiply a `UQ64x64` by a `u64`, returning a `UQ64x64`
+    pub fn mul(&self, y: u64) -> Result<Self, MathError> {
+        // vm would direct abort when overflow occured
+        let v = self.v.checked_mul(y as u128).ok_or(MathError::Overflow)?;
+        Ok(UQ64x64 { v })
+    }
+
+    /// Divide a `UQ64x64` by a `u64`, returning a `UQ64x64`.
+    pub fn div(&self, y: u64) -> Result<Self, MathError> {
+        if y == 0 {
+            return Err(MathError::DivideByZero);
+        }
+        Ok(UQ64x64 {
+            v: self.v / y as u128,
+        })
+    }
+
+    /// Returns a `UQ64x64` which represents the ratio of the numerator to the denominator.
+    pub fn fraction(numerator: u64, denominator: u64) -> Result<Self, MathError> {
+        if denominator == 0 {
+            return Err(MathError::DivideByZero);
+        }
+        let r = numerator as u128 * Q64;
+        Ok(UQ64x64 {
+            v: r / denominator as u128,
+        })
+    }
+
+    /// Compare two `UQ64x64` numbers, `EQUAL`, `LESS_THAN` or `GREATER_THAN` as on chain
+    ///
+    /// The derived `Ord` orders by the raw value too, this keeps the Move return codes.
+    pub fn compare(&self, other: &Self) -> u8 {
+        if self.v == other.v {
+            EQUAL
+        } else if self.v < other.v {
+            LESS_THAN
+        } else {
+            GREATER_THAN
+        }
+    }
+
+    /// Check if `UQ64x64` is zero
+    pub fn is_zero(&self) -> bool {
+        self.v == 0
+    }
+
+    /// Lossy conversion for display and analytics
+    pub fn to_f64(&self) -> f64 {
+        self.v as f64 / Q64 as f64
+    }
+}
+
+impl fmt::Display for UQ64x64 {
+    /// Decimal representation, `{:.N}` picks the number of fractional digits (default and max 18)
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let digits = f.precision().unwrap_or(18).min(18);
+        let int = self.v / Q64;
+        if digits == 0 {
+            return write!(f, "{}", int);
+        }
+        let frac = (self.v % Q64) * 10u128.pow(digits as u32) / Q64;
+        write!(f, "{}.{:0width$}", int, frac, width = digits)
+    }
+}
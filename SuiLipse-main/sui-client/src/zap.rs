@@ -0,0 +1,192 @@
+//! Single-sided deposits: swap part of one token, then `add_liquidity` with both.
+//!
+//! For an ideal pool the portion to swap follows from keeping the post-swap
+//! reserves in the ratio of the remaining deposit, see [`optimal_swap_amount`].
+//! `add_liquidity_` however prices the deposit with `amm_math::quote`, which
+//! truncates the reserve ratio, so [`zap_x`] and [`zap_y`] search the split that
+//! leaves the least dust under the on-chain rules instead. The truncation still
+//! mints LP against the truncated side only and donates the rest of the deposit to
+//! the pool; [`Zap::value`] and [`Zap::loss_bps`] report what comes back.
+use crate::{
+    amm_math::{self, u256_to_u64, MathError, FEE_SCALING},
+    isqrt::Sqrt,
+    simulator::{AddLiquidity, AmmError, PoolSimulator, Swap},
+};
+use ethnum::U256;
+
+/// Result of a simulated zap
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Zap {
+    /// `swap_token_x` for [`zap_x`], `swap_token_y` for [`zap_y`]
+    pub swap: Swap,
+    /// `add_liquidity` with the rest of the input and the swap output
+    pub add: AddLiquidity,
+    /// pool after both transactions
+    pub pool: PoolSimulator,
+    pub amount_in: u64,
+    /// LP share and both refunds in input token, at the spot price before the zap
+    pub value: u64,
+}
+
+impl Zap {
+    /// part of the input not coming back as LP or refund, in basis points: the swap
+    /// fee, the price impact and what the truncated `quote` donates to the pool
+    pub fn loss_bps(&self) -> u64 {
+        let loss = self.amount_in.saturating_sub(self.value) as u128;
+        (loss * FEE_SCALING as u128 / self.amount_in as u128) as u64
+    }
+}
+
+/// Portion of `amount_in` to swap so that an exact-ratio deposit uses everything
+/// s = ( sqrt( x^2 * (S + r)^2 + 4 * r * S * a * x ) - x * (S + r) ) / (2 * r), at r = S - f
+pub fn optimal_swap_amount(amount_in: u64, reserve_in: u64, f: u64) -> Result<u64, MathError> {
+    if reserve_in == 0 {
+        return Err(MathError::ReservesEmpty);
+    }
+    if amount_in == 0 {
+        return Err(MathError::InsufficientInput);
+    }
+    if f >= FEE_SCALING {
+        return Err(MathError::InvalidFee);
+    }
+
+    let s = U256::from(FEE_SCALING);
+    let r = U256::from(FEE_SCALING - f);
+    let x = U256::from(reserve_in);
+    let b = x * (s + r);
+    let root = (b * b + U256::from(4u8) * r * s * U256::from(amount_in) * x).sqrt_floor();
+
+    u256_to_u64((root - b) / (U256::from(2u8) * r))
+}
+
+/// `opt_b` of `add_liquidity_` after swapping `s` of the input, `None` when the swap itself fails
+fn quote_after_swap(
+    pool: &PoolSimulator,
+    amount_in: u64,
+    s: u64,
+    x_for_y: bool,
+) -> Option<(u64, u64)> {
+    let mut pool = *pool;
+    let out = if x_for_y {
+        pool.swap_x(s)
+    } else {
+        pool.swap_y(s)
+    }
+    .ok()?
+    .output;
+    let (token_x, token_y) = if x_for_y {
+        (amount_in - s, out)
+    } else {
+        (out, amount_in - s)
+    };
+    let opt_b = amm_math::quote(pool.reserve_x(), pool.reserve_y(), token_x).ok()?;
+
+    Some((opt_b, token_y))
+}
+
+fn execute(start: &PoolSimulator, amount_in: u64, s: u64, x_for_y: bool) -> Result<Zap, AmmError> {
+    let mut pool = *start;
+    let swap = if x_for_y {
+        pool.swap_x(s)?
+    } else {
+        pool.swap_y(s)?
+    };
+    let add = if x_for_y {
+        pool.add_liquidity(amount_in - s, swap.output, 0, 0)?
+    } else {
+        pool.add_liquidity(swap.output, amount_in - s, 0, 0)?
+    };
+
+    // price both sides in input token at the spot price before the zap
+    let (reserve_in, reserve_out) = if x_for_y {
+        (start.reserve_x(), start.reserve_y())
+    } else {
+        (start.reserve_y(), start.reserve_x())
+    };
+    let (lp_in, lp_out, refund_in, refund_out) = {
+        let (x, y, lp_supply) = pool.reserves();
+        let share = |reserve: u64| {
+            U256::from(add.lp_output as u128 * reserve as u128) / U256::from(lp_supply.max(1))
+        };
+        if x_for_y {
+            (share(x), share(y), add.refund_x, add.refund_y)
+        } else {
+            (share(y), share(x), add.refund_y, add.refund_x)
+        }
+    };
+    let out_side =
+        (lp_out + U256::from(refund_out)) * U256::from(reserve_in) / U256::from(reserve_out.max(1));
+    let value = u256_to_u64(lp_in + U256::from(refund_in) + out_side)?;
+
+    Ok(Zap {
+        swap,
+        add,
+        pool,
+        amount_in,
+        value,
+    })
+}
+
+/// Smallest `s` in `[lo, hi]` with `pred(s)`, for a predicate that turns from false to true
+fn first_true(mut lo: u64, mut hi: u64, pred: impl Fn(u64) -> bool) -> Option<u64> {
+    if lo > hi || !pred(hi) {
+        return None;
+    }
+    while lo < hi {
+        let mid = lo + (hi - lo) / 2;
+        if pred(mid) {
+            hi = mid;
+        } else {
+            lo = mid + 1;
+        }
+    }
+    Some(lo)
+}
+
+/// Deposit `amount_x` of X only
+///
+/// The least swap whose output covers `opt_b` keeps all of X in the pool and
+/// refunds the smallest remainder of Y. That is also the split minting the most
+/// LP, but with a truncated ratio part of the X is donated, see [`Zap::loss_bps`].
+pub fn zap_x(pool: &PoolSimulator, amount_x: u64) -> Result<Zap, AmmError> {
+    if amount_x < 2 {
+        return Err(AmmError::ZeroAmount);
+    }
+    let estimate = optimal_swap_amount(amount_x, pool.reserve_x(), pool.fee_percentage())?
+        .clamp(1, amount_x - 1);
+    // false for small swaps and true from some point on
+    let covered = |s| matches!(quote_after_swap(pool, amount_x, s, true), Some((b, y)) if b <= y);
+    let s = if covered(estimate) {
+        first_true(1, estimate, covered)
+    } else {
+        first_true(estimate + 1, amount_x - 1, covered)
+    }
+    .unwrap_or(estimate);
+
+    execute(pool, amount_x, s, true)
+}
+
+/// Deposit `amount_y` of Y only
+///
+/// The largest swap whose output is still priced under the remaining Y takes the
+/// first branch of `add_liquidity_`, which keeps the whole swap output in the pool.
+pub fn zap_y(pool: &PoolSimulator, amount_y: u64) -> Result<Zap, AmmError> {
+    if amount_y < 2 {
+        return Err(AmmError::ZeroAmount);
+    }
+    let estimate = optimal_swap_amount(amount_y, pool.reserve_y(), pool.fee_percentage())?
+        .clamp(1, amount_y - 1);
+    // true for small swaps and false from some point on
+    let covered = |s| matches!(quote_after_swap(pool, amount_y, s, false), Some((b, y)) if b <= y);
+    let s = if covered(estimate) {
+        first_true(estimate + 1, amount_y - 1, |s| !covered(s))
+            .map(|s| s - 1)
+            .unwrap_or(amount_y - 1)
+    } else {
+        first_true(1, estimate, |s| !covered(s))
+            .map(|s| s.max(2) - 1)
+            .unwrap_or(estimate)
+    };
+
+    execute(pool, amount_y, s, false)
+}
@@ -1,3 +1,5 @@
+use sui_lipse::amm_math::{self, MathError, FEE_SCALING};
+use sui_lipse::simulator::{AmmError, PoolSimulator, MAX_POOL_VALUE};
 use sui_lipse::*;
 
 const SUI: u64 = 1_000_000;
@@ -9,7 +11,164 @@ fn test_sqrt() {
     let token_y = 50_000;
     let lp = sqrt(SUI) * sqrt(TOKEN_Y);
     println!("get lp\n{}", lp);
-    let output = minted_lp_after_increase_liquidity(SUI, TOKEN_Y, sui, token_y, lp);
+    let output = minted_lp_after_increase_liquidity(SUI, TOKEN_Y, sui, token_y, lp).unwrap();
 
     println!("get input{}", output);
 }
+
+#[test]
+fn test_amm_math_matches_move_tests() {
+    // amm_test::test_swap_token_y_
+    assert_eq!(amm_math::get_output(5_000_000, TOKEN_Y, SUI, 3, FEE_SCALING), Ok(4973));
+    // amm_test::add_liquidity_
+    let lp = amm_math::get_l(SUI, TOKEN_Y);
+    assert_eq!(minted_lp_after_increase_liquidity(SUI, TOKEN_Y, 50, 50_000, lp), Ok(1581));
+}
+
+#[test]
+fn test_amm_math_checked() {
+    // (10000 - 3) * dx * y overflows u64, the Move VM aborts even though the output fits
+    let max = u64::MAX / 10_000;
+    assert_eq!(get_input(max, max, max, 3), Err(MathError::Overflow));
+    assert_eq!(
+        minted_lp_after_increase_liquidity(SUI, TOKEN_Y, u64::MAX / 2, 1, 3),
+        Err(MathError::Overflow)
+    );
+    assert_eq!(withdraw_liquidity(u64::MAX / 2, TOKEN_Y, 3, 4), Err(MathError::Overflow));
+
+    assert_eq!(get_input(0, SUI, TOKEN_Y, 3), Err(MathError::InsufficientInput));
+    assert_eq!(get_input(1, 0, TOKEN_Y, 3), Err(MathError::ReservesEmpty));
+    assert_eq!(get_input(1, SUI, TOKEN_Y, 10_001), Err(MathError::InvalidFee));
+    assert_eq!(quote(1, u64::MAX, 2), Err(MathError::Overflow));
+    assert_eq!(
+        withdraw_liquidity(SUI, TOKEN_Y, 1, u64::MAX),
+        Err(MathError::InsufficientLiquidityBurned)
+    );
+    assert_eq!(withdraw_liquidity(SUI, TOKEN_Y, 0, 1), Err(MathError::ZeroAmount));
+    assert_eq!(MathError::ReservesEmpty.abort_code(), Some(1));
+}
+
+#[test]
+fn test_simulator_follows_amm_test() {
+    let (mut pool, lp) = PoolSimulator::create(SUI, TOKEN_Y, 3).unwrap();
+    assert_eq!(lp, amm_math::get_l(SUI, TOKEN_Y));
+
+    let swap = pool.swap_x(5000).unwrap();
+    assert_eq!(
+        swap.output,
+        amm_math::get_output(5000, SUI, TOKEN_Y, 3, FEE_SCALING).unwrap()
+    );
+    assert_eq!(pool.reserves(), (SUI + 5000, TOKEN_Y - swap.output, lp));
+
+    let removed = pool.remove_liquidity(lp, 0, 0).unwrap();
+    assert_eq!(
+        (removed.amount_x, removed.amount_y),
+        (SUI + 5000, TOKEN_Y - swap.output)
+    );
+    assert_eq!(pool.reserves(), (0, 0, 0));
+}
+
+#[test]
+fn test_simulator_add_liquidity() {
+    let (mut pool, _) = PoolSimulator::create(SUI, TOKEN_Y, 3).unwrap();
+
+    let added = pool.add_liquidity(50, 60_000, 50, 50_000).unwrap();
+    assert_eq!(added.lp_output, 1581);
+    assert_eq!((added.refund_x, added.refund_y), (0, 10_000));
+
+    let before = pool;
+    assert_eq!(
+        pool.add_liquidity(50, 60_000, 0, 60_000),
+        Err(AmmError::InsufficientBAmount)
+    );
+    assert_eq!(pool, before);
+
+    assert_eq!(
+        PoolSimulator::create(SUI, TOKEN_Y, 0).unwrap_err(),
+        AmmError::InvalidFee
+    );
+    assert_eq!(
+        PoolSimulator::create(MAX_POOL_VALUE, 1, 3).unwrap_err(),
+        AmmError::FullPool
+    );
+    assert_eq!(pool.swap_y(0), Err(AmmError::ZeroAmount));
+}
+
+#[test]
+fn test_simulator_aborts_on_u64_overflow() {
+    let (mut pool, _) = PoolSimulator::create(MAX_POOL_VALUE - 1, MAX_POOL_VALUE - 1, 3).unwrap();
+    let before = pool;
+
+    // (10000 - 3) * dx * reserve_out of `get_output`
+    assert_eq!(pool.swap_x(1_000), Err(AmmError::Math(MathError::Overflow)));
+    assert_eq!(pool.swap_y(1_000), Err(AmmError::Math(MathError::Overflow)));
+    // amount_a * lp_supply of `add_liquidity_`
+    assert_eq!(
+        pool.add_liquidity(100_000, 100_000, 0, 0),
+        Err(AmmError::Math(MathError::Overflow))
+    );
+    // res_x * lp_value of `withdraw_liquidity`
+    assert_eq!(
+        pool.remove_liquidity(100_000, 0, 0),
+        Err(AmmError::Math(MathError::Overflow))
+    );
+    assert_eq!(pool, before);
+}
+
+#[test]
+fn test_exact_output() {
+    let dx = get_input_for_output(4973, TOKEN_Y, SUI, 3).unwrap();
+    assert!(get_input(dx, TOKEN_Y, SUI, 3).unwrap() >= 4973);
+    assert!(get_input(dx - 1, TOKEN_Y, SUI, 3).unwrap() < 4973);
+
+    let quote = amm_math::quote_exact_output(1_000_000, SUI, TOKEN_Y, 30, 50).unwrap();
+    assert_eq!(
+        quote.max_amount_in,
+        amm_math::max_amount_in(quote.amount_in, 50).unwrap()
+    );
+    assert!(quote.max_amount_in >= quote.amount_in * 10_050 / 10_000);
+
+    assert_eq!(
+        get_input_for_output(TOKEN_Y, SUI, TOKEN_Y, 3),
+        Err(MathError::InsufficientLiquidity)
+    );
+}
+
+#[test]
+fn test_quote_rounding_modes() {
+    use amm_math::{mul_div, quote_gap, quote_with_mode, QuoteMode, Rounding};
+
+    // reserve_b < reserve_a truncates the ratio to zero on chain
+    assert_eq!(quote(TOKEN_Y, SUI, 5_000), Ok(0));
+    assert_eq!(
+        quote_with_mode(TOKEN_Y, SUI, 5_000, QuoteMode::OnChain),
+        Ok(0)
+    );
+    let precise = |r| quote_with_mode(TOKEN_Y, SUI, 5_500, QuoteMode::Precise(r));
+    assert_eq!(precise(Rounding::Floor), Ok(5));
+    assert_eq!(precise(Rounding::Ceil), Ok(6));
+    assert_eq!(precise(Rounding::Nearest), Ok(6));
+
+    // 3 * 1 / 2 = 1.5, 2 * 1 / 3 = 0.67, 1 * 1 / 3 = 0.33
+    assert_eq!(mul_div(3, 1, 2, Rounding::Nearest), Ok(2));
+    assert_eq!(mul_div(2, 1, 3, Rounding::Nearest), Ok(1));
+    assert_eq!(mul_div(1, 1, 3, Rounding::Nearest), Ok(0));
+    assert_eq!(mul_div(4, 1, 2, Rounding::Ceil), Ok(2));
+    assert_eq!(
+        mul_div(1, 1, 0, Rounding::Floor),
+        Err(MathError::DivideByZero)
+    );
+    // the product is not truncated to u64
+    assert_eq!(
+        mul_div(u64::MAX, u64::MAX, u64::MAX, Rounding::Floor),
+        Ok(u64::MAX)
+    );
+
+    let gap = quote_gap(3_000, 10_000, 600).unwrap();
+    assert_eq!(gap.on_chain, 1_800);
+    assert_eq!(gap.precise, 2_000);
+    assert_eq!(gap.gap(), 200);
+    let exact = quote_gap(SUI, TOKEN_Y, 50).unwrap();
+    assert_eq!(exact.on_chain, 50_000);
+    assert_eq!(exact.gap(), 0);
+}
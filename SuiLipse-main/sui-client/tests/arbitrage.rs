@@ -0,0 +1,75 @@
+use sui_lipse::arbitrage::{cycles, find_opportunities, optimal_input};
+use sui_lipse::router::{RoutePool, Router};
+use sui_sdk::types::base_types::ObjectID;
+
+const SUI: &str = "0x2::sui::SUI";
+const USDC: &str = "0x3::usdc::USDC";
+const ETH: &str = "0x4::eth::ETH";
+
+fn pool(id: &str, x: &str, y: &str, reserve_x: u64, reserve_y: u64) -> RoutePool {
+    RoutePool {
+        id: ObjectID::from_hex_literal(id).unwrap(),
+        token_x: x.to_string(),
+        token_y: y.to_string(),
+        reserve_x,
+        reserve_y,
+        fee_percentage: 30,
+    }
+}
+
+#[test]
+fn test_balanced_pools_have_no_arbitrage() {
+    let router = Router::new(vec![
+        pool("0x1", SUI, USDC, 1_000_000, 2_000_000),
+        pool("0x2", USDC, SUI, 4_000_000, 2_000_000),
+        pool("0x3", SUI, ETH, 1_000_000, 1_000_000),
+        pool("0x4", ETH, USDC, 1_000_000, 2_000_000),
+    ]);
+    // 2 directions of {0, 1}, {0, 3, 2} and {1, 3, 2}
+    assert_eq!(cycles(&router, 3).len(), 6);
+    assert!(find_opportunities(&router, 3).is_empty());
+}
+
+#[test]
+fn test_same_pair_price_gap() {
+    // SUI is worth 2 USDC in the first pool and 3 USDC in the second, the reserves are
+    // small enough for the u64 products of `get_output` not to abort
+    let router = Router::new(vec![
+        pool("0x1", SUI, USDC, 1_000_000, 2_000_000),
+        pool("0x2", SUI, USDC, 1_000_000, 3_000_000),
+    ]);
+    let opportunities = find_opportunities(&router, 2);
+    assert_eq!(opportunities.len(), 1);
+
+    let best = &opportunities[0];
+    // buy SUI where it is cheap, sell it where it is expensive
+    assert_eq!(best.route.hops[0].token_in, USDC);
+    assert_eq!(best.route.hops[0].pool, router.pools()[0].id);
+    assert_eq!(best.route.hops[1].token_out, USDC);
+    assert_eq!(best.profit, best.route.amount_out - best.route.amount_in);
+
+    // moving the input either way earns less
+    let path = [0, 1];
+    for amount in [best.route.amount_in - 1_000, best.route.amount_in + 1_000].iter() {
+        let route = router.quote_exact_in(USDC, &path, *amount).unwrap();
+        assert!(route.amount_out - amount <= best.profit);
+    }
+}
+
+#[test]
+fn test_triangle() {
+    // SUI -> USDC -> ETH -> SUI gains roughly 10% before fees
+    let router = Router::new(vec![
+        pool("0x1", SUI, USDC, 1_000_000_000, 2_000_000_000),
+        pool("0x2", USDC, ETH, 2_000_000_000, 1_000_000_000),
+        pool("0x3", ETH, SUI, 1_000_000_000, 1_100_000_000),
+    ]);
+    let best = optimal_input(&router, SUI, &[0, 1, 2]).unwrap();
+    assert_eq!(best.route.hops.len(), 3);
+    assert!(best.profit > 0);
+    assert!(optimal_input(&router, SUI, &[2, 1, 0]).is_none());
+
+    let opportunities = find_opportunities(&router, 3);
+    assert_eq!(opportunities.len(), 1);
+    assert_eq!(opportunities[0], best);
+}
@@ -0,0 +1,95 @@
+use sui_lipse::amm_math::MathError;
+use sui_lipse::concentrated::*;
+
+const Q64: u128 = 1 << 64;
+
+#[test]
+fn test_tick_math() {
+    assert_eq!(get_sqrt_price_at_tick(0), Ok(Q64));
+    assert_eq!(get_sqrt_price_at_tick(MIN_TICK), Ok(MIN_SQRT_PRICE));
+    assert_eq!(get_sqrt_price_at_tick(MAX_TICK), Ok(MAX_SQRT_PRICE));
+    assert_eq!(
+        get_sqrt_price_at_tick(MAX_TICK + 1),
+        Err(ConcentratedError::InvalidTick(MAX_TICK + 1))
+    );
+
+    for tick in [MIN_TICK, -100_000, -1, 0, 1, 60, 23_028, MAX_TICK] {
+        let sqrt_price = get_sqrt_price_at_tick(tick).unwrap();
+        assert_eq!(get_tick_at_sqrt_price(sqrt_price), Ok(tick));
+        if tick < MAX_TICK {
+            assert_eq!(get_tick_at_sqrt_price(sqrt_price + 1), Ok(tick));
+        }
+    }
+    // 1.0001^23028 ~ 10
+    let price = (get_sqrt_price_at_tick(23_028).unwrap() as f64 / Q64 as f64).powi(2);
+    assert!((price - 10.0).abs() < 0.01);
+}
+
+#[test]
+fn test_liquidity_amounts_round_trip() {
+    let sqrt_price = get_sqrt_price_at_tick(0).unwrap();
+    let sqrt_a = get_sqrt_price_at_tick(-600).unwrap();
+    let sqrt_b = get_sqrt_price_at_tick(600).unwrap();
+
+    let liquidity =
+        get_liquidity_for_amounts(sqrt_price, sqrt_a, sqrt_b, 1_000_000, 1_000_000).unwrap();
+    let (x, y) = get_amounts_for_liquidity(sqrt_price, sqrt_a, sqrt_b, liquidity, true).unwrap();
+    assert!(x <= 1_000_000 && y <= 1_000_000);
+    assert!(x >= 999_990 && y >= 999_990);
+
+    // out of range positions hold a single token
+    let (x, y) = get_amounts_for_liquidity(sqrt_a, sqrt_price, sqrt_b, liquidity, false).unwrap();
+    assert_eq!(y, 0);
+    assert!(x > 0);
+}
+
+#[test]
+fn test_swap_crosses_ticks() {
+    let mut pool = ConcentratedPool::new(Q64, 30, 60).unwrap();
+    let (x, y) = pool.update_position(-600, 600, 1_000_000_000).unwrap();
+    assert!(x > 0 && y > 0);
+    pool.update_position(-1200, -600, 2_000_000_000).unwrap();
+    assert_eq!(pool.liquidity(), 1_000_000_000);
+    // i128::MIN has no positive counterpart for the upper tick
+    assert_eq!(
+        pool.clone().update_position(-600, 600, i128::MIN),
+        Err(ConcentratedError::Math(MathError::Overflow))
+    );
+
+    let before = pool.clone();
+    let result = pool
+        .swap(true, SwapAmount::ExactIn(60_000_000), MIN_SQRT_PRICE + 1)
+        .unwrap();
+    assert!(result.tick < -600 && result.tick >= -1200);
+    assert_eq!(result.steps.len(), 2);
+    assert_eq!(result.amount_in, 60_000_000);
+    assert!(result.amount_out > 0 && result.amount_out < 60_000_000);
+    assert!(result.fee_paid >= 60_000_000 * 30 / 10_000);
+    assert_eq!(pool.liquidity(), 2_000_000_000);
+
+    // exact output of the same trade costs at most the same input
+    let mut again = before.clone();
+    let exact_out = again
+        .swap(
+            true,
+            SwapAmount::ExactOut(result.amount_out),
+            MIN_SQRT_PRICE + 1,
+        )
+        .unwrap();
+    assert_eq!(exact_out.amount_out, result.amount_out);
+    assert!(exact_out.amount_in <= result.amount_in + 2);
+
+    // the way back stops at the price limit
+    let limit = get_sqrt_price_at_tick(0).unwrap();
+    let back = pool
+        .swap(false, SwapAmount::ExactIn(u64::MAX / 2), limit)
+        .unwrap();
+    assert_eq!(back.sqrt_price, limit);
+    assert_eq!(pool.tick(), 0);
+    assert_eq!(pool.liquidity(), 1_000_000_000);
+
+    assert_eq!(
+        pool.swap(true, SwapAmount::ExactIn(1), limit + 1),
+        Err(ConcentratedError::InvalidPriceLimit(limit + 1))
+    );
+}
@@ -0,0 +1,82 @@
+use sui_lipse::amm_math::{self, MathError, FEE_SCALING};
+use sui_lipse::curve::{curve_for_verifier, ConstantProduct, ConstantSum, Curve, StableSwap};
+use sui_lipse::uq64x64::UQ64x64;
+
+const RESERVE: u64 = 1_000_000_000;
+
+#[test]
+fn test_constant_product_is_amm_math() {
+    let curve = ConstantProduct;
+    assert_eq!(
+        curve.get_output(5_000_000, RESERVE, 1_000_000, 3),
+        amm_math::get_output(5_000_000, RESERVE, 1_000_000, 3, FEE_SCALING)
+    );
+    assert_eq!(
+        curve.minted_lp(0, 0, 1_000_000, RESERVE, 0),
+        Ok(amm_math::get_l(1_000_000, RESERVE))
+    );
+    assert_eq!(
+        curve.spot_price(1_000_000, RESERVE).unwrap().decode(),
+        Ok(1000)
+    );
+}
+
+#[test]
+fn test_constant_sum() {
+    let curve = ConstantSum;
+    assert_eq!(curve.get_output(10_000, RESERVE, RESERVE, 30), Ok(9_970));
+    let input = curve.get_input(9_970, RESERVE, RESERVE, 30).unwrap();
+    assert!(curve.get_output(input, RESERVE, RESERVE, 30).unwrap() >= 9_970);
+    assert_eq!(
+        curve.get_output(RESERVE, RESERVE, RESERVE, 0),
+        Err(MathError::InsufficientLiquidity)
+    );
+    assert_eq!(curve.spot_price(1, RESERVE), Ok(UQ64x64::encode(1)));
+}
+
+#[test]
+fn test_stable_swap() {
+    let stable = StableSwap::new(100);
+    let product = ConstantProduct;
+    // the constant product aborts on u64 products at RESERVE
+    let reserve = 1_000_000;
+    let trade = reserve / 10;
+
+    // flatter than x * y = k around the peg
+    let out = stable.get_output(trade, reserve, reserve, 4).unwrap();
+    assert!(out > product.get_output(trade, reserve, reserve, 4).unwrap());
+    assert!(out < trade);
+
+    let input = stable.get_input(out, reserve, reserve, 4).unwrap();
+    assert!(stable.get_output(input, reserve, reserve, 4).unwrap() >= out);
+    assert!(stable.get_output(input - 2, reserve, reserve, 4).unwrap() < out);
+
+    let balanced = stable.spot_price(reserve, reserve).unwrap().to_f64();
+    assert!((balanced - 1.0).abs() < 1e-9);
+    let skewed = stable.spot_price(reserve * 2, reserve).unwrap().to_f64();
+    assert!(skewed < 1.0 && skewed > 0.5);
+
+    let lp = stable.minted_lp(0, 0, reserve, reserve, 0).unwrap();
+    assert_eq!(lp, 2 * reserve);
+    assert_eq!(
+        stable.minted_lp(reserve, reserve, reserve, reserve, lp),
+        Ok(lp)
+    );
+
+    assert_eq!(StableSwap::new(0).get_d(1, 1), Err(MathError::InvalidAmp));
+
+    // 4A'xxy does not fit in 256 bits
+    let (x, y) = (u64::MAX, u64::MAX / 2);
+    let steep = StableSwap::new(u64::MAX);
+    assert!(steep.get_d(x, y).is_ok());
+    assert_eq!(steep.spot_price(x, y), Err(MathError::Overflow));
+}
+
+#[test]
+fn test_curve_for_verifier() {
+    let curve = curve_for_verifier("0x2::amm_pair::StableCurve", 100).unwrap();
+    assert!(curve.get_output(1_000, RESERVE, RESERVE, 4).unwrap() > 990);
+    assert!(curve_for_verifier("AMM_V2", 0).is_some());
+    assert!(curve_for_verifier("0x2::amm_pair::AMM_V3", 0).is_none());
+    assert!(curve_for_verifier("0x2::coin::Coin", 0).is_none());
+}
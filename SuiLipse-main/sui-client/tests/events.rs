@@ -0,0 +1,153 @@
+use serde_json::json;
+use std::str::FromStr;
+use sui_lipse::events::{AmmEvent, EventError, LiquidityAddedEvent, PoolCreatedEvent, SwapEvent};
+use sui_lipse::oracle;
+use sui_lipse::simulator::PoolSimulator;
+use sui_lipse::type_tag::TypeTag;
+use sui_sdk::types::base_types::{ObjectID, SuiAddress};
+
+const PKG: &str = "0x000000000000000000000000000000000000008a";
+const POOL: &str = "0x0000000000000000000000000000000000000021";
+const CREATOR: &str = "0x00000000000000000000000000000000000000a1";
+
+fn event_type(name: &str) -> String {
+    format!(
+        "{}::amm::{}<{}::verifier::V, 0x2::sui::SUI, 0x3::coin::Coin<0x3::usdc::USDC>>",
+        PKG, name, PKG
+    )
+}
+
+#[test]
+fn test_decode_pool_events() {
+    let event = AmmEvent::decode(
+        &event_type("SwapEvent"),
+        json!({ "coin0_in": 1000, "coin1_out": 996 }),
+    )
+    .unwrap();
+    let ty = event.pool_type().unwrap();
+    assert_eq!(ty.token_x, TypeTag::from_str("0x2::sui::SUI").unwrap());
+    assert_eq!(
+        ty.token_y,
+        TypeTag::from_str("0x3::coin::Coin<0x3::usdc::USDC>").unwrap()
+    );
+    assert!(matches!(
+        event,
+        AmmEvent::Swap(
+            _,
+            SwapEvent {
+                coin0_in: 1000,
+                coin1_out: 996
+            }
+        )
+    ));
+
+    let event = AmmEvent::decode(
+        &event_type("LiquidityAddedEvent"),
+        json!({ "added_amount_0": 10, "added_amount_1": 20, "lp_tokens_received": 14 }),
+    )
+    .unwrap();
+    assert!(matches!(
+        event,
+        AmmEvent::LiquidityAdded(
+            _,
+            LiquidityAddedEvent {
+                added_amount_0: 10,
+                added_amount_1: 20,
+                lp_tokens_received: 14
+            }
+        )
+    ));
+
+    // u128 fields come as decimal strings
+    let event = AmmEvent::decode(
+        &event_type("OracleUpdatedEvent"),
+        json!({
+            "last_price_cumulative_0": "340282366920938463463374607431768211455",
+            "last_price_cumulative_1": 7
+        }),
+    )
+    .unwrap();
+    match event {
+        AmmEvent::OracleUpdated(_, e) => {
+            assert_eq!(e.last_price_cumulative_0, u128::MAX);
+            let observation = oracle::Observation::from_event(&e, 42);
+            assert_eq!(observation.price_y_cumulative, 7);
+        }
+        _ => panic!("expected OracleUpdated, got {:?}", event),
+    }
+}
+
+#[test]
+fn test_liquidity_added_field_order() {
+    let mut pool = PoolSimulator::from_reserves(1_000_000, 2_000_000, 1_414_000, 3);
+    let added = pool.add_liquidity(1_000, 3_000, 0, 0).unwrap();
+    assert_eq!((added.amount_x, added.amount_y), (1_000, 2_000));
+
+    // `add_liquidity` emits the LP minted first and Y last
+    let event: LiquidityAddedEvent = serde_json::from_value(json!({
+        "added_amount_0": added.lp_output,
+        "added_amount_1": added.amount_x,
+        "lp_tokens_received": added.amount_y
+    }))
+    .unwrap();
+    assert_eq!(event.amount_x(), added.amount_x);
+    assert_eq!(event.amount_y(), added.amount_y);
+    assert_eq!(event.lp_output(), added.lp_output);
+    assert_eq!(event.lp_output(), 1_414);
+}
+
+#[test]
+fn test_decode_admin_events() {
+    let event = AmmEvent::decode(
+        &format!("{}::amm::PoolCreatedEvent", PKG),
+        json!({ "pool_id": { "bytes": POOL }, "creator": CREATOR }),
+    )
+    .unwrap();
+    let creator: SuiAddress = serde_json::from_value(json!(CREATOR)).unwrap();
+    assert_eq!(
+        event,
+        AmmEvent::PoolCreated(PoolCreatedEvent {
+            pool_id: ObjectID::from_hex_literal("0x21").unwrap(),
+            creator,
+        })
+    );
+    assert_eq!(event.pool_type(), None);
+
+    // the id may also come flattened
+    let event = AmmEvent::decode(
+        &format!("{}::amm::PoolCapabilityCreatedEvent", PKG),
+        json!({ "pool_capability_id": POOL }),
+    )
+    .unwrap();
+    assert!(matches!(event, AmmEvent::PoolCapabilityCreated(_)));
+}
+
+#[test]
+fn test_decode_errors() {
+    assert!(matches!(
+        AmmEvent::decode("0x2::coin::CurrencyCreated<0x2::sui::SUI>", json!({})),
+        Err(EventError::UnknownEvent(_))
+    ));
+    assert!(matches!(
+        AmmEvent::decode(&format!("{}::amm::FlashLoanEvent", PKG), json!({})),
+        Err(EventError::UnknownEvent(_))
+    ));
+    assert!(matches!(
+        AmmEvent::decode(
+            &format!("{}::amm::SwapEvent<0x2::sui::SUI>", PKG),
+            json!({ "coin0_in": 1, "coin1_out": 1 })
+        ),
+        Err(EventError::TypeArguments(_))
+    ));
+    assert!(matches!(
+        AmmEvent::decode(&format!("{}::amm::SwapEvent<V, X, Y>", PKG), json!({})),
+        Err(EventError::TypeTag(_))
+    ));
+    assert!(matches!(
+        AmmEvent::decode(
+            &event_type("SwapEvent"),
+            json!({ "coin0_in": 1, "coin1_out": -1 })
+        ),
+        Err(EventError::Json(_))
+    ));
+}
@@ -0,0 +1,74 @@
+use sui_lipse::amm_math::MathError;
+use sui_lipse::fees::{FeeLedger, FeeSwitchPool, ProtocolFee};
+use sui_lipse::simulator::PoolSimulator;
+
+/// small enough for `(10000 - f) * dx * reserve` to fit in `u64` as on chain
+const RESERVE: u64 = 1_000_000;
+
+fn trade(pool: &mut FeeSwitchPool, rounds: usize) {
+    for _ in 0..rounds {
+        let out = pool.swap_x(RESERVE / 10).unwrap().output;
+        pool.swap_y(out).unwrap();
+    }
+}
+
+#[test]
+fn test_mint_fee_matches_uniswap() {
+    let one_sixth = ProtocolFee::new(1, 6).unwrap();
+    // sqrt(k) grows from 100 to 110 over a supply of 1000: 1000 * 10 / (5 * 110 + 100)
+    assert_eq!(one_sixth.mint_fee(110, 110, 1_000, 100 * 100), Ok(15));
+    assert_eq!(one_sixth.mint_fee(110, 110, 1_000, 0), Ok(0));
+    assert_eq!(one_sixth.mint_fee(90, 90, 1_000, 100 * 100), Ok(0));
+    assert_eq!(ProtocolFee::new(2, 1), Err(MathError::InvalidFee));
+    assert_eq!(ProtocolFee::new(1, 0), Err(MathError::DivideByZero));
+}
+
+#[test]
+fn test_fee_switch_takes_protocol_share() {
+    let (pool, lp) = PoolSimulator::create(RESERVE, RESERVE, 30).unwrap();
+    let mut off = FeeSwitchPool::new(pool, None);
+    let mut on = FeeSwitchPool::new(pool, Some(ProtocolFee::new(1, 6).unwrap()));
+    trade(&mut off, 20);
+    trade(&mut on, 20);
+
+    assert_eq!(off.pending_protocol_lp(), Ok(0));
+    assert!(on.pending_protocol_lp().unwrap() > 0);
+
+    // the creator withdraws everything
+    let lp_only = off.remove_liquidity(lp, 0, 0).unwrap();
+    let with_switch = on.remove_liquidity(lp, 0, 0).unwrap();
+    assert!(with_switch.amount_x < lp_only.amount_x);
+    assert!(on.treasury_lp() > 0);
+
+    // round trips leave Y where it was, all fees end up as growth of X; at a price
+    // close to 1 the treasury holds roughly a sixth of that value across both tokens
+    let (treasury_x, treasury_y) = on.treasury_value().unwrap();
+    let fee_growth_x = lp_only.amount_x - RESERVE;
+    let share = (treasury_x + treasury_y) as f64 / fee_growth_x as f64;
+    assert!((share - 1f64 / 6f64).abs() < 0.01, "share {}", share);
+}
+
+#[test]
+fn test_collect_and_ledger() {
+    let (pool, _) = PoolSimulator::create(RESERVE, RESERVE, 30).unwrap();
+    let mut on = FeeSwitchPool::new(pool, Some(ProtocolFee::new(1, 6).unwrap()));
+    trade(&mut on, 3);
+
+    let pending = on.pending_protocol_lp().unwrap();
+    assert_eq!(on.collect(), Ok(pending));
+    assert_eq!(on.pending_protocol_lp(), Ok(0));
+    assert_eq!(on.treasury_lp(), pending);
+
+    let ledger = on.ledger();
+    assert_eq!(ledger.swaps, 6);
+    assert_eq!(ledger.volume_x, 3 * RESERVE as u128 / 10);
+    assert_eq!(ledger.fees_x, 3 * 300);
+    let protocol = Some(ProtocolFee::new(1, 6).unwrap());
+    assert_eq!(ledger.protocol_fees(protocol).0, 150);
+    assert_eq!(ledger.lp_fees(protocol).0, 750);
+    assert_eq!(ledger.lp_fees(None), (ledger.fees_x, ledger.fees_y));
+
+    let mut ledger = FeeLedger::default();
+    ledger.record(false, 10_000, 30);
+    assert_eq!((ledger.fees_x, ledger.fees_y), (0, 30));
+}
@@ -0,0 +1,105 @@
+use sui_lipse::amm_math::MathError;
+use sui_lipse::first_deposit::{
+    analyze, simulate_inflation_attack, LockMode, MINIMUM_LIQUIDITY, UNISWAP_V2_MINIMUM_LIQUIDITY,
+};
+use sui_lipse::simulator::AmmError;
+
+const SUI: u64 = 1_000_000;
+const TOKEN_Y: u64 = 1_000_000_000;
+const FEE: u64 = 3;
+
+#[test]
+fn test_first_deposit_report() {
+    let report = analyze(SUI, TOKEN_Y, FEE, LockMode::None, 1_000).unwrap();
+    // 1000 * 31622, see `amm_test::add_liquidity_`
+    assert_eq!(report.lp_supply, 31_622_000);
+    assert_eq!(report.lp_minted, 31_622_000);
+    assert_eq!(report.locked, 0);
+    assert!(report.share_price_x.to_f64() < 1f64);
+    assert_eq!(
+        report.inflation_cost_x,
+        1_000 * 31_622_000 + 1 - SUI as u128
+    );
+    assert_eq!(report.locked_cost_x, 0);
+
+    let locked = analyze(
+        SUI,
+        TOKEN_Y,
+        FEE,
+        LockMode::MinimumLiquidity(UNISWAP_V2_MINIMUM_LIQUIDITY),
+        1_000,
+    )
+    .unwrap();
+    assert_eq!(locked.lp_minted, 31_622_000 - UNISWAP_V2_MINIMUM_LIQUIDITY);
+    assert_eq!(locked.share_price_x, report.share_price_x);
+    assert!(locked.locked_cost_x > 0);
+
+    // the first deposit has to exceed the locked shares
+    assert_eq!(
+        analyze(100, 100, FEE, LockMode::MinimumLiquidity(100), 1).unwrap_err(),
+        AmmError::Math(MathError::InsufficientLiquidity)
+    );
+}
+
+#[test]
+fn test_inflation_attack_without_lock_captures_victim() {
+    let outcome = simulate_inflation_attack(SUI, SUI, FEE, LockMode::None).unwrap();
+    assert_eq!(outcome.victim_lp, 0);
+    // only X is inflated, Y stays at the dust deposit
+    assert_eq!(outcome.attacker_cost_x, SUI + 1);
+    assert_eq!(outcome.attacker_cost_y, 1);
+    // the attacker leaves with the victim's X, the unused Y is refunded to the victim
+    assert_eq!(outcome.profit(), (SUI as i128, 0));
+
+    // the smallest deposit minting more than u64::MAX shares does not fit in u64
+    assert_eq!(
+        simulate_inflation_attack(SUI, SUI, FEE, LockMode::MinimumLiquidity(u64::MAX)).unwrap_err(),
+        AmmError::Math(MathError::Overflow)
+    );
+}
+
+#[test]
+fn test_minimum_liquidity_makes_attack_unprofitable() {
+    let outcome = simulate_inflation_attack(
+        SUI,
+        SUI,
+        FEE,
+        LockMode::MinimumLiquidity(UNISWAP_V2_MINIMUM_LIQUIDITY),
+    )
+    .unwrap();
+    assert_eq!(outcome.victim_lp, 0);
+    // 32 * 32 is the smallest square deposit minting more than 1000 shares
+    assert_eq!(outcome.attacker_cost_x, SUI * 1024 + 1);
+    let (profit_x, profit_y) = outcome.profit();
+    assert!(profit_x < -(SUI as i128) * 900);
+    assert!(profit_y < 0);
+}
+
+#[test]
+fn test_amm_minimum_liquidity() {
+    let report = analyze(
+        SUI,
+        TOKEN_Y,
+        FEE,
+        LockMode::MinimumLiquidity(MINIMUM_LIQUIDITY),
+        1_000,
+    )
+    .unwrap();
+    assert_eq!(report.lp_minted, 31_622_000 - 10);
+
+    // 4 * 4 is the smallest square deposit minting more than 10 shares, 6 of them to the attacker
+    let outcome =
+        simulate_inflation_attack(SUI, SUI, FEE, LockMode::MinimumLiquidity(MINIMUM_LIQUIDITY))
+            .unwrap();
+    assert_eq!(outcome.victim_lp, 0);
+    assert_eq!(outcome.attacker_cost_x, SUI * 16 + 1);
+    let (profit_x, profit_y) = outcome.profit();
+    assert!(profit_x < -(SUI as i128) * 9);
+    assert!(profit_y < 0);
+
+    // the fee of the pool is checked like `create_pool_`
+    assert_eq!(
+        analyze(SUI, TOKEN_Y, 0, LockMode::None, 1_000).unwrap_err(),
+        AmmError::InvalidFee
+    );
+}
@@ -0,0 +1,64 @@
+use sui_lipse::amm_math::MathError;
+use sui_lipse::uq128x128::{self, UQ128x128, Q128};
+use sui_lipse::uq64x64::{self, UQ64x64, Q64};
+
+#[test]
+fn test_uq64x64() {
+    let quarter = UQ64x64::fraction(100, 400).unwrap();
+    assert_eq!(quarter.to_u128(), Q64 / 4);
+    assert_eq!(quarter.decode(), Ok(0));
+    assert_eq!(quarter.mul(8).unwrap().decode(), Ok(1));
+    assert_eq!(UQ64x64::encode(7).div(7).unwrap(), UQ64x64::encode(1));
+    assert!(quarter < UQ64x64::encode(1));
+    assert_eq!(quarter.compare(&UQ64x64::encode(1)), uq64x64::LESS_THAN);
+    assert_eq!(quarter.compare(&quarter), uq64x64::EQUAL);
+    assert_eq!(UQ64x64::encode(1).compare(&quarter), uq64x64::GREATER_THAN);
+
+    assert_eq!(UQ64x64::fraction(1, 0), Err(MathError::DivideByZero));
+    assert_eq!(UQ64x64::encode(u64::MAX).mul(2), Err(MathError::Overflow));
+    assert_eq!(format!("{:.4}", UQ64x64::fraction(3, 2).unwrap()), "1.4999");
+}
+
+#[test]
+fn test_uq128x128() {
+    let quarter = UQ128x128::fraction(100, 400).unwrap();
+    assert_eq!(quarter.to_u256(), Q128 / 4);
+    assert_eq!(UQ128x128::encode(u128::MAX).decode(), Ok(u128::MAX));
+    assert_eq!(
+        UQ128x128::encode(u128::MAX).mul(2),
+        Err(MathError::Overflow)
+    );
+    assert!(UQ128x128::default().is_zero());
+    assert_eq!(quarter.compare(&UQ128x128::encode(1)), uq128x128::LESS_THAN);
+    assert_eq!(quarter.compare(&quarter), uq128x128::EQUAL);
+}
+
+#[test]
+fn test_fixed_point_serde() {
+    let uq = UQ64x64::fraction(1, 3).unwrap();
+    let json = serde_json::to_string(&uq).unwrap();
+    assert_eq!(json, format!("{{\"v\":\"{}\"}}", uq.to_u128()));
+    assert_eq!(serde_json::from_str::<UQ64x64>(&json).unwrap(), uq);
+    assert_eq!(
+        serde_json::from_str::<UQ64x64>("{\"v\":42}").unwrap(),
+        UQ64x64::from_raw(42)
+    );
+
+    let uq = UQ128x128::fraction(1, 3).unwrap();
+    let json = serde_json::to_string(&uq).unwrap();
+    assert_eq!(serde_json::from_str::<UQ128x128>(&json).unwrap(), uq);
+}
+
+#[test]
+fn test_fixed_point_bcs() {
+    // Move `u128` and `u256` are little-endian in BCS
+    let uq = UQ64x64::fraction(1, 3).unwrap();
+    let bytes = bcs::to_bytes(&uq).unwrap();
+    assert_eq!(bytes, uq.to_u128().to_le_bytes());
+    assert_eq!(bcs::from_bytes::<UQ64x64>(&bytes).unwrap(), uq);
+
+    let uq = UQ128x128::fraction(1, 3).unwrap();
+    let bytes = bcs::to_bytes(&uq).unwrap();
+    assert_eq!(bytes, uq.to_u256().to_le_bytes());
+    assert_eq!(bcs::from_bytes::<UQ128x128>(&bytes).unwrap(), uq);
+}
@@ -0,0 +1,101 @@
+use ethnum::U256;
+use sui_lipse::amm_math::{self, Rounding};
+use sui_lipse::isqrt::{get_l, LiquidityMode, Sqrt};
+
+/// `amm_math::sqrt` as written in Move
+fn move_sqrt(y: u64) -> u64 {
+    if y < 4 {
+        if y == 0 {
+            0
+        } else {
+            1
+        }
+    } else {
+        let mut z = y;
+        let mut x = y / 2 + 1;
+        while x < z {
+            z = x;
+            x = (y / x + x) / 2;
+        }
+        z
+    }
+}
+
+#[test]
+fn test_sqrt_u64_matches_move() {
+    let samples = (0..10_000u64)
+        .chain((1..64).map(|s| 1u64 << s))
+        .chain((1..64).map(|s| (1u64 << s) - 1))
+        .chain((2..100_000u64).map(|i| i * i - 1))
+        .chain([u64::MAX, u64::MAX - 1, 4_294_967_295 * 4_294_967_295]);
+    for y in samples {
+        assert_eq!(y.sqrt_floor(), move_sqrt(y), "sqrt({})", y);
+    }
+}
+
+#[test]
+fn test_sqrt_rounding() {
+    assert_eq!(0u64.sqrt_ceil(), 0);
+    assert_eq!(16u64.sqrt_floor(), 4);
+    assert_eq!(16u64.sqrt_ceil(), 4);
+    assert_eq!(17u64.sqrt_ceil(), 5);
+    // 4.5^2 = 20.25
+    assert_eq!(20u64.sqrt_nearest(), 4);
+    assert_eq!(21u64.sqrt_nearest(), 5);
+    assert_eq!(u64::MAX.sqrt_floor(), u32::MAX as u64);
+    assert_eq!(u64::MAX.sqrt_ceil(), 1 << 32);
+    assert_eq!(17u64.sqrt_rounded(Rounding::Floor), 4);
+
+    assert_eq!(u128::MAX.sqrt_floor(), u64::MAX as u128);
+    assert_eq!(u128::MAX.sqrt_ceil(), 1 << 64);
+    let r = 3_037_000_499u128 * 1_000_003;
+    assert_eq!((r * r).sqrt_floor(), r);
+    assert_eq!((r * r - 1).sqrt_floor(), r - 1);
+    assert_eq!((r * r + 1).sqrt_ceil(), r + 1);
+
+    assert_eq!(U256::MAX.sqrt_floor(), U256::from(u128::MAX));
+    assert_eq!(U256::MAX.sqrt_ceil(), U256::ONE << 128u32);
+    let r = U256::from(u128::MAX - 12_345);
+    assert_eq!((r * r).sqrt_floor(), r);
+    assert_eq!((r * r - 1).sqrt_floor(), r - 1);
+    assert_eq!((r * r + r).sqrt_nearest(), r);
+    assert_eq!((r * r + r + 1).sqrt_nearest(), r + 1);
+}
+
+#[test]
+fn test_get_l_modes() {
+    // 1_000_000 and 1_000_000_000 -> 1000 * 31622, while sqrt(10^15) = 31622776.6
+    assert_eq!(
+        get_l(1_000_000, 1_000_000_000, LiquidityMode::Move),
+        31_622_000
+    );
+    assert_eq!(amm_math::get_l(1_000_000, 1_000_000_000), 31_622_000);
+    assert_eq!(
+        amm_math::get_l(1_000_000, 1_000_000_000),
+        sui_lipse::sqrt(1_000_000) * sui_lipse::sqrt(1_000_000_000)
+    );
+    assert_eq!(
+        get_l(
+            1_000_000,
+            1_000_000_000,
+            LiquidityMode::Exact(Rounding::Floor)
+        ),
+        31_622_776
+    );
+    assert_eq!(
+        get_l(
+            1_000_000,
+            1_000_000_000,
+            LiquidityMode::Exact(Rounding::Nearest)
+        ),
+        31_622_777
+    );
+    assert_eq!(
+        get_l(u64::MAX, u64::MAX, LiquidityMode::Exact(Rounding::Ceil)),
+        u64::MAX
+    );
+    assert_eq!(
+        get_l(u64::MAX, u64::MAX, LiquidityMode::Move),
+        (u32::MAX as u64) * (u32::MAX as u64)
+    );
+}
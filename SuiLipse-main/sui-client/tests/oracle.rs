@@ -0,0 +1,52 @@
+use sui_lipse::oracle::{twap, Observation, OracleError, OracleUpdatedEvent};
+use sui_lipse::uq64x64::UQ64x64;
+
+const SUI: u64 = 1_000_000;
+const TOKEN_Y: u64 = 1_000_000_000;
+
+#[test]
+fn test_twap_over_constant_reserves() {
+    let start = Observation {
+        timestamp: 1,
+        price_x_cumulative: 0,
+        price_y_cumulative: 0,
+    };
+    let end = start.update(SUI, TOKEN_Y, 11).unwrap();
+    let avg = twap(&start, &end).unwrap();
+
+    assert_eq!(avg.elapsed, 10);
+    assert_eq!(avg.price_x, UQ64x64::fraction(TOKEN_Y, SUI).unwrap());
+    assert_eq!(avg.price_x.decode(), Ok(1000));
+    assert_eq!(avg.price_y, UQ64x64::fraction(SUI, TOKEN_Y).unwrap());
+}
+
+#[test]
+fn test_twap_wraps_like_overflow_add() {
+    let start = Observation {
+        timestamp: 100,
+        price_x_cumulative: u128::MAX - 5,
+        price_y_cumulative: u128::MAX,
+    };
+    let mid = start.update(SUI, TOKEN_Y, 110).unwrap();
+    let end = mid.update(SUI * 2, TOKEN_Y, 130).unwrap();
+    assert!(mid.price_x_cumulative < start.price_x_cumulative);
+
+    let avg = twap(&start, &end).unwrap();
+    let expected = (UQ64x64::fraction(TOKEN_Y, SUI).unwrap().to_u128() * 10
+        + UQ64x64::fraction(TOKEN_Y, SUI * 2).unwrap().to_u128() * 20)
+        / 30;
+    assert_eq!(avg.price_x.to_u128(), expected);
+
+    assert_eq!(twap(&end, &start), Err(OracleError::EmptyWindow(130, 100)));
+}
+
+#[test]
+fn test_observation_from_event() {
+    let event: OracleUpdatedEvent = serde_json::from_str(
+        r#"{"last_price_cumulative_0":"340282366920938463463374607431768211455","last_price_cumulative_1":7}"#,
+    )
+    .unwrap();
+    let obs = Observation::from_event(&event, 42);
+    assert_eq!(obs.price_x_cumulative, u128::MAX);
+    assert_eq!(obs.price_y_cumulative, 7);
+}
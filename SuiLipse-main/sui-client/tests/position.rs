@@ -0,0 +1,60 @@
+use sui_lipse::position::{analyze, impermanent_loss, EntrySnapshot};
+use sui_lipse::simulator::PoolSimulator;
+
+/// small enough for `(10000 - f) * dx * reserve` to fit in `u64` as on chain
+const RESERVE: u64 = 1_000_000;
+
+#[test]
+fn test_untouched_pool() {
+    let (pool, lp) = PoolSimulator::create(RESERVE, 4 * RESERVE, 30).unwrap();
+    let entry = EntrySnapshot::new(pool, lp / 10).unwrap();
+    let report = analyze(lp / 10, &pool, &entry).unwrap();
+
+    assert_eq!(
+        (report.amount_x, report.amount_y),
+        (entry.amount_x, entry.amount_y)
+    );
+    assert_eq!(report.fee_x, 0);
+    assert_eq!(report.fee_y, 0);
+    assert_eq!(report.impermanent_loss, 0f64);
+    assert_eq!(report.value_in_x, 2 * report.amount_x as u128);
+    assert_eq!(report.value_in_y, 2 * report.amount_y as u128);
+    assert_eq!(report.hold_value_in_x, report.value_in_x);
+}
+
+#[test]
+fn test_impermanent_loss_without_fees() {
+    let pool = PoolSimulator::from_reserves(RESERVE, RESERVE, RESERVE, 0);
+    let entry = EntrySnapshot::new(pool, RESERVE / 2).unwrap();
+
+    // doubling the reserve of X quarters the price of X
+    let mut moved = pool;
+    moved.swap_x(RESERVE).unwrap();
+    let report = analyze(RESERVE / 2, &moved, &entry).unwrap();
+
+    let expected = impermanent_loss(0.25);
+    assert!((expected + 0.2).abs() < 1e-12);
+    assert!((report.impermanent_loss - expected).abs() < 1e-6);
+    assert!(report.fee_x <= 1 && report.fee_y <= 1);
+    assert!(report.value_in_x < report.hold_value_in_x);
+}
+
+#[test]
+fn test_fees_accrue_to_position() {
+    let (pool, lp) = PoolSimulator::create(RESERVE, RESERVE, 30).unwrap();
+    let entry = EntrySnapshot::new(pool, lp).unwrap();
+
+    // round trips leave the price roughly where it was but grow the reserves
+    let mut traded = pool;
+    for _ in 0..10 {
+        let out = traded.swap_x(RESERVE / 10).unwrap().output;
+        traded.swap_y(out).unwrap();
+    }
+    let report = analyze(lp, &traded, &entry).unwrap();
+
+    assert!(report.amount_x > entry.amount_x);
+    assert!(report.fee_x > 0 && report.fee_y > 0);
+    // without fees the pool would hold close to the deposits
+    assert!((report.amount_x - report.fee_x) as f64 / (entry.amount_x as f64) < 1.01);
+    assert!(report.impermanent_loss <= 0f64 && report.impermanent_loss > -0.01);
+}
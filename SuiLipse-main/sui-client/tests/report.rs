@@ -0,0 +1,60 @@
+use sui_lipse::amm_math::MathError;
+use sui_lipse::get_input;
+use sui_lipse::report::{min_amount_out, QuoteReport};
+use sui_lipse::uq64x64::UQ64x64;
+
+const RESERVE_X: u64 = 10_000_000;
+const RESERVE_Y: u64 = 100_000_000;
+
+#[test]
+fn test_report_matches_get_input() {
+    let report = QuoteReport::new(100_000, RESERVE_X, RESERVE_Y, 30, 50).unwrap();
+
+    assert_eq!(
+        report.amount_out,
+        get_input(100_000, RESERVE_X, RESERVE_Y, 30).unwrap()
+    );
+    assert_eq!(report.spot_price_before.decode(), Ok(10));
+    assert!(report.spot_price_after < report.spot_price_before);
+    assert!(report.effective_price < report.spot_price_before);
+    assert_eq!(report.fee_paid, 300);
+    // 1% of the reserve moves the price by roughly 1%
+    assert_eq!(report.price_impact_bps, 99);
+    assert_eq!(report.min_received, report.amount_out * 9950 / 10000);
+    assert_eq!(
+        report.spot_price_after,
+        UQ64x64::fraction(RESERVE_Y - report.amount_out, RESERVE_X + 100_000).unwrap()
+    );
+}
+
+#[test]
+fn test_small_trade_impact() {
+    let report = QuoteReport::new(1_000, RESERVE_X, RESERVE_Y, 0, 0).unwrap();
+    assert_eq!(report.amount_out, 9_999);
+    assert_eq!(report.price_impact_bps, 1);
+    assert_eq!(report.fee_paid, 0);
+    assert_eq!(report.min_received, 9_999);
+
+    // truncation of the output alone is 1% of a tiny trade
+    let dust = QuoteReport::new(10, RESERVE_X, RESERVE_Y, 0, 0).unwrap();
+    assert_eq!(dust.amount_out, 99);
+    assert_eq!(dust.price_impact_bps, 100);
+}
+
+#[test]
+fn test_exact_output_report() {
+    let report = QuoteReport::exact_output(1_000_000, RESERVE_X, RESERVE_Y, 30, 100).unwrap();
+    assert_eq!(report.amount_out, 1_000_000);
+    assert!(get_input(report.amount_in, RESERVE_X, RESERVE_Y, 30).unwrap() >= 1_000_000);
+    assert_eq!(report.min_received, 990_000);
+    assert!(report
+        .to_string()
+        .contains("minimum received:  990000 (1.00% slippage)"));
+}
+
+#[test]
+fn test_min_amount_out() {
+    assert_eq!(min_amount_out(1_000, 0), Ok(1_000));
+    assert_eq!(min_amount_out(999, 50), Ok(994));
+    assert_eq!(min_amount_out(1_000, 10_001), Err(MathError::InvalidFee));
+}
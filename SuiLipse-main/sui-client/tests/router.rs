@@ -0,0 +1,106 @@
+use sui_lipse::amm_math::{get_output, FEE_SCALING};
+use sui_lipse::router::{RoutePool, Router, RouterError};
+use sui_sdk::types::base_types::ObjectID;
+
+const SUI: &str = "0x2::sui::SUI";
+const USDC: &str = "0x3::usdc::USDC";
+const ETH: &str = "0x4::eth::ETH";
+const BTC: &str = "0x5::btc::BTC";
+
+fn pool(id: &str, x: &str, y: &str, reserve_x: u64, reserve_y: u64) -> RoutePool {
+    RoutePool {
+        id: ObjectID::from_hex_literal(id).unwrap(),
+        token_x: x.to_string(),
+        token_y: y.to_string(),
+        reserve_x,
+        reserve_y,
+        fee_percentage: 30,
+    }
+}
+
+fn router() -> Router {
+    Router::new(vec![
+        // thin direct pool
+        pool("0x1", SUI, USDC, 1_000_000, 1_000_000),
+        // deep two-hop path through ETH
+        pool("0x2", SUI, ETH, 100_000_000, 100_000_000),
+        pool("0x3", USDC, ETH, 100_000_000, 100_000_000),
+        pool("0x4", ETH, BTC, 50_000, 50_000),
+    ])
+}
+
+#[test]
+fn test_exact_in_prefers_deeper_multi_hop() {
+    let router = router();
+    let direct = router.best_exact_in(SUI, USDC, 100_000, 1).unwrap();
+    let best = router.best_exact_in(SUI, USDC, 100_000, 3).unwrap();
+
+    assert_eq!(direct.hops.len(), 1);
+    assert_eq!(
+        direct.amount_out,
+        get_output(100_000, 1_000_000, 1_000_000, 30, FEE_SCALING).unwrap()
+    );
+    assert_eq!(best.hops.len(), 2);
+    assert!(best.amount_out > direct.amount_out);
+    assert!(best.price_impact_bps < direct.price_impact_bps);
+
+    // hops chain into each other, the second pool is traded Y -> X
+    assert_eq!(best.hops[0].amount_out, best.hops[1].amount_in);
+    assert_eq!(best.hops[0].token_out, ETH);
+    assert!(best.hops[0].x_to_y);
+    assert!(!best.hops[1].x_to_y);
+    assert_eq!(best.amount_in, 100_000);
+    assert_eq!(best.amount_out, best.hops[1].amount_out);
+}
+
+#[test]
+fn test_exact_out_round_trips_exact_in() {
+    let router = router();
+    let route = router.best_exact_out(SUI, USDC, 50_000, 3).unwrap();
+    assert_eq!(route.amount_out, 50_000);
+    assert_eq!(route.hops.len(), 2);
+
+    let path = [1, 2];
+    let forward = router.quote_exact_in(SUI, &path, route.amount_in).unwrap();
+    assert!(forward.amount_out >= 50_000);
+    let short = router
+        .quote_exact_in(SUI, &path, route.amount_in - 1)
+        .unwrap();
+    assert!(short.amount_out < 50_000);
+}
+
+#[test]
+fn test_no_route() {
+    let router = router();
+    assert_eq!(
+        router.best_exact_in(SUI, BTC, 1_000, 1).unwrap_err(),
+        RouterError::NoRoute(SUI.to_string(), BTC.to_string())
+    );
+    assert_eq!(
+        router.best_exact_in(SUI, BTC, 1_000, 2).unwrap().hops.len(),
+        2
+    );
+    // more than the pool holds
+    assert!(router.best_exact_out(SUI, BTC, 50_000, 3).is_err());
+}
+
+#[test]
+fn test_invalid_path() {
+    let router = router();
+    assert_eq!(
+        router.quote_exact_in(SUI, &[0, 7], 1_000).unwrap_err(),
+        RouterError::UnknownPool(7)
+    );
+    assert_eq!(
+        router.quote_exact_out(SUI, &[9], 1_000).unwrap_err(),
+        RouterError::UnknownPool(9)
+    );
+    // the ETH pool does not hold USDC
+    assert_eq!(
+        router.quote_exact_out(USDC, &[3], 1_000).unwrap_err(),
+        RouterError::TokenNotInPool {
+            token: USDC.to_string(),
+            pool: router.pools()[3].id,
+        }
+    );
+}
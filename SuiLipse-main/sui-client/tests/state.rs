@@ -0,0 +1,509 @@
+use std::str::FromStr;
+use sui_lipse::simulator::PoolSimulator;
+use sui_lipse::state::{
+    AnyType, Balance, Card, CardCollection, CoinState, CopyCard, Field, Guardians, MoveState,
+    MoveType, Pool, PoolCapability, PoolIdsList, PoolType, StateError, VecSet, SBT,
+};
+use sui_lipse::type_tag::{StructTag, TypeTag};
+use sui_sdk::types::{
+    base_types::{ObjectID, SuiAddress},
+    id::ID,
+};
+
+const POOL_TYPE: &str =
+    "0x8a::amm::Pool<0x8a::verifier::V, 0x2::sui::SUI, 0x3::coin::Coin<0x3::usdc::USDC>>";
+
+struct Sui;
+impl MoveType for Sui {
+    fn move_type() -> Option<String> {
+        Some("0x2::sui::SUI".to_string())
+    }
+}
+
+struct Eth;
+impl MoveType for Eth {
+    fn move_type() -> Option<String> {
+        Some("0x4::eth::ETH".to_string())
+    }
+}
+
+fn pool_json(reserve_x: &str, lp_supply: &str) -> String {
+    format!(
+        r#"{{
+            "id": {{ "id": {{ "bytes": "0x000000000000000000000000000000000000005c" }} }},
+            "reserve_x": {},
+            "reserve_y": "2000",
+            "lp_supply": {},
+            "fee_percentage": 30,
+            "last_block_timestamp": 42,
+            "last_price_x_cumulative": "340282366920938463463374607431768211455",
+            "last_price_y_cumulative": "7",
+            "locked": false,
+            "emergency": true
+        }}"#,
+        reserve_x, lp_supply
+    )
+}
+
+#[test]
+fn test_pool_deserialize() {
+    let pool: Pool = serde_json::from_str(&pool_json(r#"{ "value": 1000 }"#, "1414")).unwrap();
+    // `ObjectID` only parses the full 20 bytes of an address
+    assert_eq!(pool.id(), ObjectID::from_hex_literal("0x5c").unwrap());
+    assert_eq!(pool.reserves(), (1000, 2000, 1414));
+    assert_eq!(pool.fee_percentage(), 30);
+    assert_eq!(pool.last_block_timestamp(), 42);
+    assert_eq!(pool.last_price_x_cumulative(), u128::MAX);
+    assert_eq!(pool.last_price_y_cumulative(), 7);
+    assert!(!pool.is_locked());
+    assert!(pool.is_emergency());
+
+    let observation = pool.observation();
+    assert_eq!(observation.timestamp, 42);
+    assert_eq!(observation.price_x_cumulative, u128::MAX);
+
+    let sim = PoolSimulator::from(&pool);
+    assert_eq!(sim.reserves(), (1000, 2000, 1414));
+}
+
+#[test]
+fn test_pool_typed_parameters() {
+    let pool: Pool<AnyType, Sui, Eth> =
+        serde_json::from_str(&pool_json("1000", r#"{ "value": "1414" }"#)).unwrap();
+    assert_eq!(pool.reserve_x(), 1000);
+    assert_eq!(pool.lp_supply(), 1414);
+
+    assert!(serde_json::from_str::<Pool>(&pool_json(r#"{ "balance": 1 }"#, "1")).is_err());
+    assert!(serde_json::from_str::<Pool>(&pool_json("-1", "1")).is_err());
+}
+
+#[test]
+fn test_balance() {
+    let balance: Balance<Sui> =
+        serde_json::from_str(r#"{ "value": "18446744073709551615" }"#).unwrap();
+    assert_eq!(balance.value(), u64::MAX);
+    assert_eq!(balance, Balance::new(u64::MAX));
+}
+
+#[test]
+fn test_pool_type() {
+    let ty = PoolType::parse(POOL_TYPE).unwrap();
+    assert_eq!(ty.verifier, TypeTag::from_str("0x8a::verifier::V").unwrap());
+    assert_eq!(ty.token_x, TypeTag::from_str("0x2::sui::SUI").unwrap());
+    assert_eq!(
+        ty.token_y,
+        TypeTag::from_str("0x3::coin::Coin<0x3::usdc::USDC>").unwrap()
+    );
+    let package = ObjectID::from_hex_literal("0x8a").unwrap();
+    assert_eq!(
+        ty.pool(package).to_string(),
+        StructTag::from_str(POOL_TYPE).unwrap().to_string()
+    );
+    assert_eq!(
+        ty.lp_token(package).lp_token_types().unwrap(),
+        ty.pool(package).pool_types().unwrap()
+    );
+    assert_eq!(PoolType::from_tag(&ty.pool(package)), Ok(ty.clone()));
+
+    assert!(ty.matches::<AnyType, AnyType, AnyType>());
+    assert!(ty.matches::<AnyType, Sui, AnyType>());
+    assert!(!ty.matches::<AnyType, AnyType, Sui>());
+    assert!(!ty.matches::<AnyType, Eth, AnyType>());
+
+    assert_eq!(PoolType::parse("0x2::coin::Coin<0x2::sui::SUI>"), None);
+    assert_eq!(PoolType::parse("0x8a::amm::Pool<0x2::sui::SUI>"), None);
+    assert_eq!(PoolType::parse("0x8a::amm::Pool"), None);
+    assert_eq!(PoolType::parse("0x8a::amm::Pool<V, X, Y>"), None);
+}
+
+const GUARDIAN: &str = "0x00000000000000000000000000000000000000a1";
+const STRANGER: &str = "0x00000000000000000000000000000000000000b2";
+
+fn address(hex: &str) -> SuiAddress {
+    serde_json::from_value(serde_json::Value::String(hex.to_string())).unwrap()
+}
+
+#[test]
+fn test_guardians() {
+    let guardians: Guardians = serde_json::from_str(&format!(
+        r#"{{
+            "id": {{ "id": {{ "bytes": "0x0000000000000000000000000000000000000011" }} }},
+            "guardians": {{ "contents": ["{}"] }}
+        }}"#,
+        GUARDIAN
+    ))
+    .unwrap();
+    assert_eq!(guardians.guardians(), &[address(GUARDIAN)]);
+    assert!(guardians.is_guardian(&address(GUARDIAN)));
+    assert!(!guardians.is_guardian(&address(STRANGER)));
+
+    let cap: PoolCapability = serde_json::from_str(
+        r#"{ "id": { "id": { "bytes": "0x0000000000000000000000000000000000000012" } } }"#,
+    )
+    .unwrap();
+    assert_eq!(cap.id(), ObjectID::from_hex_literal("0x12").unwrap());
+}
+
+#[test]
+fn test_pool_ids_list() {
+    let list: PoolIdsList = serde_json::from_str(
+        r#"{
+            "id": { "id": { "bytes": "0x0000000000000000000000000000000000000013" } },
+            "pool_ids": [
+                "0x0000000000000000000000000000000000000021",
+                "0x0000000000000000000000000000000000000022"
+            ]
+        }"#,
+    )
+    .unwrap();
+    let pool = ObjectID::from_hex_literal("0x22").unwrap();
+    assert_eq!(list.id(), ObjectID::from_hex_literal("0x13").unwrap());
+    assert_eq!(list.pool_ids().len(), 2);
+    assert_eq!(list.pool_ids()[1], pool);
+    assert!(list.contains(&pool));
+    assert!(!list.contains(&ObjectID::from_hex_literal("0x23").unwrap()));
+
+    let empty: VecSet<u64> = serde_json::from_str(r#"{ "contents": [] }"#).unwrap();
+    assert!(empty.is_empty());
+    assert!(serde_json::from_str::<VecSet<u64>>(r#"{ "keys": [] }"#).is_err());
+}
+
+fn uid(n: u8) -> serde_json::Value {
+    serde_json::json!({ "id": { "bytes": format!("0x{:040x}", n) } })
+}
+
+fn card_field_json(field: u8, card: u8) -> serde_json::Value {
+    serde_json::json!({
+        "id": uid(field),
+        "name": { "bytes": format!("0x{:040x}", card) },
+        "value": {
+            "id": uid(card),
+            "name": format!("Crypto Jarek #{}", card),
+            "description": "Test NFT collection with locking features",
+            "url": { "url": format!("https://arweave.net/{}", card) }
+        }
+    })
+}
+
+fn card_field(field: u8, card: u8) -> Field<ID, Card> {
+    serde_json::from_value(card_field_json(field, card)).unwrap()
+}
+
+#[test]
+fn test_nft_state() {
+    let copy_card: CopyCard = serde_json::from_value(serde_json::json!({
+        "id": uid(1),
+        "name": "test limiilted requirement",
+        "url": "https://arweave.net/Ys5"
+    }))
+    .unwrap();
+    assert_eq!(copy_card.id(), ObjectID::from_hex_literal("0x1").unwrap());
+    assert_eq!(copy_card.url().as_str(), "https://arweave.net/Ys5");
+
+    let sbt: SBT = serde_json::from_value(serde_json::json!({
+        "id": uid(2),
+        "url": { "url": "data:image/svg+xml;base64,PHN2Zz4=" }
+    }))
+    .unwrap();
+    assert_eq!(sbt.url().to_string(), "data:image/svg+xml;base64,PHN2Zz4=");
+
+    let field = card_field(3, 4);
+    assert_eq!(field.id(), ObjectID::from_hex_literal("0x3").unwrap());
+    assert_eq!(
+        field.value().id(),
+        ObjectID::from_hex_literal("0x4").unwrap()
+    );
+    assert_eq!(field.value().name(), "Crypto Jarek #4");
+}
+
+#[test]
+fn test_card_collection() {
+    let collection: CardCollection = serde_json::from_value(serde_json::json!({
+        "id": uid(10),
+        "cards": { "contents": [
+            { "bytes": format!("0x{:040x}", 12) },
+            { "bytes": format!("0x{:040x}", 11) },
+            { "bytes": format!("0x{:040x}", 13) }
+        ] },
+        "max_capacity": 100
+    }))
+    .unwrap();
+    assert_eq!(collection.len(), 3);
+    assert_eq!(collection.max_capacity(), 100);
+    assert_eq!(
+        collection.card_ids()[0],
+        ObjectID::from_hex_literal("0xc").unwrap()
+    );
+
+    // children come in any order, one card has no field and one field is a stranger
+    let cards = collection.resolve_cards(vec![
+        card_field(21, 11),
+        card_field(22, 99),
+        card_field(23, 12),
+    ]);
+    let names: Vec<_> = cards.iter().map(Card::name).collect();
+    assert_eq!(names, ["Crypto Jarek #12", "Crypto Jarek #11"]);
+}
+
+fn package() -> ObjectID {
+    ObjectID::from_hex_literal("0x8a").unwrap()
+}
+
+#[test]
+fn test_move_state_type_check() {
+    let id = ObjectID::from_hex_literal("0x5c").unwrap();
+    let pool_type = format!(
+        "{}::amm::Pool<{}::verifier::V, 0x2::sui::SUI, 0x4::eth::ETH>",
+        package(),
+        package()
+    );
+    assert!(<Pool>::check_type(id, &pool_type, package()).is_ok());
+    assert!(Pool::<AnyType, Sui, Eth>::check_type(id, &pool_type, package()).is_ok());
+    // X and Y swapped
+    assert!(matches!(
+        Pool::<AnyType, Eth, Sui>::check_type(id, &pool_type, package()),
+        Err(StateError::TypeMismatch { .. })
+    ));
+    // same struct from another package
+    let other = ObjectID::from_hex_literal("0x8b").unwrap();
+    assert!(<Pool>::check_type(id, &pool_type, other).is_err());
+
+    assert!(CoinState::check_type(id, "0x2::coin::Coin<0x2::sui::SUI>", package()).is_ok());
+    assert!(Guardians::check_type(id, "0x2::coin::Coin<0x2::sui::SUI>", package()).is_err());
+    assert!(matches!(
+        CoinState::check_type(id, "0x2::coin::Coin<", package()),
+        Err(StateError::TypeTag(_))
+    ));
+    assert_eq!(
+        PoolCapability::expected_type(package()),
+        format!("{}::amm::PoolCapability", package())
+    );
+}
+
+#[test]
+fn test_move_state_from_fields() {
+    let id = ObjectID::from_hex_literal("0x1").unwrap();
+    let fields = serde_json::json!({ "id": uid(1), "balance": 500 });
+    let coin = CoinState::from_fields(
+        id,
+        "0x2::coin::Coin<0x2::sui::SUI>",
+        fields.clone(),
+        package(),
+    )
+    .unwrap();
+    assert_eq!(coin.balance(), 500);
+    assert_eq!(coin.uid_into(), id);
+
+    // the tag the type was checked against, to read `T` without fetching again
+    let (coin, tag) = CoinState::from_fields_with_type(
+        id,
+        "0x2::coin::Coin<0x2::sui::SUI>",
+        fields.clone(),
+        package(),
+    )
+    .unwrap();
+    assert_eq!(coin.balance(), 500);
+    assert_eq!(
+        tag.coin_type().unwrap(),
+        &TypeTag::from_str("0x2::sui::SUI").unwrap()
+    );
+
+    let err =
+        SBT::from_fields(id, "0x2::coin::Coin<0x2::sui::SUI>", fields, package()).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        format!(
+            "object {} has type 0x2::coin::Coin<0x2::sui::SUI>, expected {}::sbt::SBT",
+            id,
+            package()
+        )
+    );
+
+    let sbt_type = format!("{}::sbt::SBT", package());
+    assert!(matches!(
+        SBT::from_fields(
+            id,
+            &sbt_type,
+            serde_json::json!({ "id": uid(1) }),
+            package()
+        ),
+        Err(StateError::Deserialize(..))
+    ));
+}
+
+#[test]
+fn test_field_type_params() {
+    let id = ObjectID::from_hex_literal("0x3").unwrap();
+    let field_type =
+        |name: &str, value: &str| format!("0x2::dynamic_field::Field<{}, {}>", name, value);
+    let card = format!("{}::nft_collection::Card", package());
+
+    let field = Field::<ID, Card>::from_fields(
+        id,
+        &field_type("0x2::object::ID", &card),
+        card_field_json(3, 4),
+        package(),
+    )
+    .unwrap();
+    assert_eq!(field.id(), id);
+
+    // another value, another name or a Card of another package is not a `Field<ID, Card>`
+    for type_ in [
+        field_type(
+            "0x2::object::ID",
+            &format!("{}::nft_collection::CopyCard", package()),
+        ),
+        field_type("u64", &card),
+        field_type("0x2::object::ID", "0x8b::nft_collection::Card"),
+        field_type("0x2::object::ID", "u64"),
+    ] {
+        assert!(matches!(
+            Field::<ID, Card>::from_fields(id, &type_, card_field_json(3, 4), package()),
+            Err(StateError::TypeMismatch { .. })
+        ));
+    }
+}
+
+fn id_bytes(n: u8) -> [u8; 20] {
+    let mut bytes = [0u8; 20];
+    bytes[19] = n;
+    bytes
+}
+
+// the tuples below spell out the Move struct layouts field by field
+
+#[test]
+fn test_bcs_pool() {
+    let id = ObjectID::from_hex_literal("0x5c").unwrap();
+    let ty = format!(
+        "{}::amm::Pool<{}::verifier::V, 0x2::sui::SUI, 0x4::eth::ETH>",
+        package(),
+        package()
+    );
+    let bytes = bcs::to_bytes(&(
+        id_bytes(0x5c),
+        1000u64,
+        2000u64,
+        1414u64,
+        30u64,
+        42u64,
+        u128::MAX,
+        7u128,
+        false,
+        true,
+    ))
+    .unwrap();
+    let pool = <Pool>::from_bcs(id, &ty, &bytes, package()).unwrap();
+    assert_eq!(pool.id(), id);
+    assert_eq!(pool.reserves(), (1000, 2000, 1414));
+    assert_eq!(pool.fee_percentage(), 30);
+    assert_eq!(pool.last_block_timestamp(), 42);
+    assert_eq!(pool.last_price_x_cumulative(), u128::MAX);
+    assert_eq!(pool.last_price_y_cumulative(), 7);
+    assert!(!pool.is_locked());
+    assert!(pool.is_emergency());
+
+    // a missing trailing field
+    assert!(matches!(
+        <Pool>::from_bcs(id, &ty, &bytes[..bytes.len() - 1], package()),
+        Err(StateError::Bcs(..))
+    ));
+}
+
+#[test]
+fn test_bcs_coin_and_admin() {
+    let id = ObjectID::from_hex_literal("0x1").unwrap();
+    let bytes = bcs::to_bytes(&(id_bytes(1), 500u64)).unwrap();
+    let coin =
+        CoinState::from_bcs(id, "0x2::coin::Coin<0x2::sui::SUI>", &bytes, package()).unwrap();
+    assert_eq!(coin.balance(), 500);
+    let cap = sui_lipse::state::TreasuryCapState::from_bcs(
+        id,
+        "0x2::coin::TreasuryCap<0x2::sui::SUI>",
+        &bytes,
+        package(),
+    )
+    .unwrap();
+    assert_eq!(cap.total_supply(), 500);
+
+    let guardian = address(GUARDIAN);
+    let bytes = bcs::to_bytes(&(id_bytes(2), vec![id_bytes(0xa1)])).unwrap();
+    let guardians = Guardians::from_bcs(
+        id,
+        &format!("{}::amm::Guardians", package()),
+        &bytes,
+        package(),
+    )
+    .unwrap();
+    assert!(guardians.is_guardian(&guardian));
+
+    let bytes = bcs::to_bytes(&(id_bytes(3), vec![id_bytes(0x21), id_bytes(0x22)])).unwrap();
+    let list = PoolIdsList::from_bcs(
+        id,
+        &format!("{}::amm::PoolIdsList", package()),
+        &bytes,
+        package(),
+    )
+    .unwrap();
+    assert_eq!(
+        list.pool_ids()[0],
+        ObjectID::from_hex_literal("0x21").unwrap()
+    );
+
+    let bytes = bcs::to_bytes(&id_bytes(4)).unwrap();
+    let cap = PoolCapability::from_bcs(
+        id,
+        &format!("{}::amm::PoolCapability", package()),
+        &bytes,
+        package(),
+    )
+    .unwrap();
+    assert_eq!(cap.id(), ObjectID::from_hex_literal("0x4").unwrap());
+}
+
+#[test]
+fn test_bcs_nft() {
+    let id = ObjectID::from_hex_literal("0x1").unwrap();
+    let card = (
+        id_bytes(4),
+        "Crypto Jarek #4",
+        "locked card",
+        "https://arweave.net/4",
+    );
+
+    let bytes = bcs::to_bytes(&card).unwrap();
+    let ty = format!("{}::nft_collection::Card", package());
+    let decoded = Card::from_bcs(id, &ty, &bytes, package()).unwrap();
+    assert_eq!(decoded.name(), "Crypto Jarek #4");
+    assert_eq!(decoded.description(), "locked card");
+    assert_eq!(decoded.url().as_str(), "https://arweave.net/4");
+
+    let bytes = bcs::to_bytes(&(id_bytes(5), "copy", "https://arweave.net/5")).unwrap();
+    let ty = format!("{}::nft_collection::CopyCard", package());
+    let copy_card = CopyCard::from_bcs(id, &ty, &bytes, package()).unwrap();
+    assert_eq!(copy_card.url().as_str(), "https://arweave.net/5");
+
+    let bytes = bcs::to_bytes(&(id_bytes(6), "data:image/svg+xml;base64,")).unwrap();
+    let ty = format!("{}::sbt::SBT", package());
+    assert!(SBT::from_bcs(id, &ty, &bytes, package()).is_ok());
+
+    let bytes = bcs::to_bytes(&(id_bytes(10), vec![id_bytes(4)], 100u64)).unwrap();
+    let ty = format!(
+        "{}::nft_collection::CardCollection<{}::nft_collection::Card>",
+        package(),
+        package()
+    );
+    let collection = CardCollection::from_bcs(id, &ty, &bytes, package()).unwrap();
+    assert_eq!(collection.max_capacity(), 100);
+
+    // Field<ID, Card> { id, name, value }
+    let bytes = bcs::to_bytes(&(id_bytes(21), id_bytes(4), card)).unwrap();
+    let ty = format!(
+        "0x2::dynamic_field::Field<0x2::object::ID, {}::nft_collection::Card>",
+        package()
+    );
+    let field: Field<sui_sdk::types::id::ID, Card> =
+        MoveState::from_bcs(id, &ty, &bytes, package()).unwrap();
+    let cards = collection.resolve_cards(vec![field]);
+    assert_eq!(cards[0].name(), "Crypto Jarek #4");
+}
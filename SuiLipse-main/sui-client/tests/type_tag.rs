@@ -0,0 +1,104 @@
+use std::str::FromStr;
+use sui_lipse::type_tag::{StructTag, TypeTag, TypeTagError};
+use sui_sdk::types::base_types::ObjectID;
+
+const PKG: &str = "0x8a8f4d6e3a5b2c1d0e9f8a7b6c5d4e3f2a1b0c9d";
+
+fn tag(s: &str) -> TypeTag {
+    TypeTag::from_str(s).unwrap()
+}
+
+#[test]
+fn test_parse() {
+    let coin = StructTag::from_str("0x2::coin::Coin<0x2::sui::SUI>").unwrap();
+    assert_eq!(coin.address, ObjectID::from_hex_literal("0x2").unwrap());
+    assert_eq!(coin.qualified_name(), "coin::Coin");
+    assert_eq!(coin.coin_type().unwrap(), &tag("0x2::sui::SUI"));
+
+    let pool = StructTag::from_str(&format!(
+        "{pkg}::amm::Pool<{pkg}::verifier::V, 0x2::sui::SUI,0x3::coin::Coin< 0x3::usdc::USDC >>",
+        pkg = PKG
+    ))
+    .unwrap();
+    let params = pool.pool_types().unwrap();
+    assert_eq!(params[1], tag("0x2::sui::SUI"));
+    assert_eq!(params[2], tag("0x3::coin::Coin<0x3::usdc::USDC>"));
+
+    assert_eq!(
+        tag("vector<vector<u8>>"),
+        TypeTag::Vector(Box::new(TypeTag::Vector(Box::new(TypeTag::U8))))
+    );
+    assert_eq!(tag(" u128 "), TypeTag::U128);
+}
+
+#[test]
+fn test_build() {
+    let sui = tag("0x2::sui::SUI");
+    let coin = StructTag::coin(sui.clone());
+    assert_eq!(
+        coin,
+        StructTag::from_str("0x2::coin::Coin<0x2::sui::SUI>").unwrap()
+    );
+    assert_eq!(
+        StructTag::treasury_cap(sui.clone()).treasury_cap_type(),
+        Ok(&sui)
+    );
+
+    let package = ObjectID::from_hex_literal(PKG).unwrap();
+    let v = tag(&format!("{}::verifier::V", PKG));
+    let usdc = tag("0x3::usdc::USDC");
+    let lp = StructTag::lp_token(package, v.clone(), sui.clone(), usdc.clone());
+    assert_eq!(lp.lp_token_types().unwrap(), &[v, sui, usdc][..]);
+    assert!(lp.pool_types().is_err());
+
+    // rendering parses back to the same tag
+    let rendered = StructTag::coin(lp.clone().into()).to_string();
+    let parsed = StructTag::from_str(&rendered).unwrap();
+    assert_eq!(parsed.coin_type().unwrap().as_struct().unwrap(), &lp);
+}
+
+#[test]
+fn test_errors() {
+    assert_eq!(TypeTag::from_str("  "), Err(TypeTagError::Empty));
+    assert_eq!(
+        TypeTag::from_str("0xZZ::sui::SUI"),
+        Err(TypeTagError::InvalidAddress("0xZZ".to_string()))
+    );
+    assert_eq!(
+        TypeTag::from_str("2::sui::SUI"),
+        Err(TypeTagError::InvalidAddress("2".to_string()))
+    );
+    assert_eq!(
+        TypeTag::from_str("0x2::1sui::SUI"),
+        Err(TypeTagError::InvalidIdentifier("1sui".to_string()))
+    );
+    assert!(matches!(
+        TypeTag::from_str("0x2::coin::Coin<0x2::sui::SUI"),
+        Err(TypeTagError::Unexpected {
+            expected: ", or >",
+            position: 29,
+            ..
+        })
+    ));
+    assert!(matches!(
+        TypeTag::from_str("0x2::sui::SUI>"),
+        Err(TypeTagError::Unexpected {
+            expected: "end of input",
+            ..
+        })
+    ));
+    assert!(matches!(
+        TypeTag::from_str("vector<>"),
+        Err(TypeTagError::Unexpected {
+            expected: "a type",
+            ..
+        })
+    ));
+
+    let not_a_coin = StructTag::from_str("0x3::coin::Coin<0x2::sui::SUI>").unwrap();
+    assert!(matches!(
+        not_a_coin.coin_type(),
+        Err(TypeTagError::WrongType { .. })
+    ));
+    assert!(TypeTag::U64.as_struct().is_err());
+}
@@ -0,0 +1,59 @@
+use sui_lipse::amm_math::MathError;
+use sui_lipse::simulator::PoolSimulator;
+use sui_lipse::zap::{optimal_swap_amount, zap_x, zap_y};
+
+/// small enough for `(10000 - f) * dx * reserve` to fit in `u64` as on chain
+const SUI: u64 = 1_000_000;
+
+#[test]
+fn test_optimal_swap_amount() {
+    // without fee half of a tiny deposit is swapped, sqrt(x^2 + a x) - x in general
+    assert_eq!(optimal_swap_amount(2_000, SUI, 0), Ok(999));
+    assert_eq!(optimal_swap_amount(SUI * 3, SUI, 0), Ok(SUI));
+    // the fee makes the swap slightly larger
+    let with_fee = optimal_swap_amount(10_000_000, SUI, 30).unwrap();
+    assert!(with_fee > optimal_swap_amount(10_000_000, SUI, 0).unwrap());
+    assert_eq!(
+        optimal_swap_amount(1, SUI, 10_000),
+        Err(MathError::InvalidFee)
+    );
+}
+
+#[test]
+fn test_zap_x_leaves_little_dust() {
+    let (pool, _) = PoolSimulator::create(SUI, 10 * SUI, 30).unwrap();
+    let zap = zap_x(&pool, 10_000).unwrap();
+
+    assert_eq!(zap.add.refund_x, 0);
+    assert!(zap.add.refund_y < 100);
+    assert_eq!(zap.swap.input + zap.add.amount_x, 10_000);
+    assert!(zap.add.lp_output > 0);
+
+    // after the swap `quote` truncates the reserve ratio of 9.9 to 9, so the
+    // on-chain split is smaller than the ideal one
+    let estimate = optimal_swap_amount(10_000, SUI, 30).unwrap();
+    assert!(zap.swap.input < estimate);
+
+    // and LP is only minted for 9 / 9.9 of the X deposited, the rest is donated
+    let donated = zap.add.amount_x as u128 * 9 / 99;
+    assert_eq!(zap.amount_in, 10_000);
+    assert!(zap.value < zap.amount_in);
+    assert!(zap.loss_bps() as u128 >= donated * 10_000 / zap.amount_in as u128);
+    assert!(zap.loss_bps() < 600, "loss {}", zap.loss_bps());
+}
+
+#[test]
+fn test_zap_y_keeps_swap_output() {
+    let (pool, _) = PoolSimulator::create(SUI, 10 * SUI, 30).unwrap();
+    let zap = zap_y(&pool, 100_000).unwrap();
+
+    assert_eq!(zap.add.refund_x, 0);
+    assert_eq!(zap.add.amount_x, zap.swap.output);
+    assert!(zap.add.refund_y < 100);
+    let (x, y, _) = zap.pool.reserves();
+    assert_eq!(y, 10 * SUI + 100_000 - zap.add.refund_y);
+    assert_eq!(x, SUI);
+
+    // the ratio of 10 is exact here, only the fee and the price impact are lost
+    assert!(zap.loss_bps() < 100, "loss {}", zap.loss_bps());
+}